@@ -1,30 +1,30 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
 use winit::{
     application::ApplicationHandler,
-    event::{ElementState, KeyEvent, MouseScrollDelta, WindowEvent},
+    event::WindowEvent,
     event_loop::ActiveEventLoop,
-    keyboard::{KeyCode, PhysicalKey},
     window::{Window, WindowId},
 };
 
 use crate::{
     content::{
         WithGPUBuffer,
+        controller::CameraController,
         model_object::{generate_ground, generate_teapot},
         world::World,
     },
-    math::algebra::matrix::Matrix,
     render::web_gpu::WebGpuContext,
 };
 
-const STEP: f32 = 10.0;
-
 #[derive(Default)]
 pub struct App<'w> {
     window: Option<Arc<Window>>,
     web_gpu_context: Option<WebGpuContext<'w>>,
     world: Option<World>,
+    controller: CameraController,
+    // time of last frame while the camera keeps moving
+    last_frame: Option<Instant>,
 }
 
 impl<'w> ApplicationHandler for App<'w> {
@@ -62,6 +62,12 @@ impl<'w> ApplicationHandler for App<'w> {
         _window_id: WindowId,
         event: WindowEvent,
     ) {
+        if (self.controller.process_event(&event) || self.controller.is_active())
+            && let Some(window) = self.window.as_ref()
+        {
+            window.request_redraw();
+        }
+
         match event {
             WindowEvent::CloseRequested => event_loop.exit(),
             WindowEvent::Resized(size) => {
@@ -76,49 +82,26 @@ impl<'w> ApplicationHandler for App<'w> {
                 }
             }
             WindowEvent::RedrawRequested => {
-                if let (Some(world), Some(web_gpu_context)) =
-                    (self.world.as_mut(), self.web_gpu_context.as_mut())
-                {
+                if let (Some(window), Some(world), Some(web_gpu_context)) = (
+                    self.window.as_ref(),
+                    self.world.as_mut(),
+                    self.web_gpu_context.as_mut(),
+                ) {
+                    let now = Instant::now();
+                    let dt = self
+                        .last_frame
+                        .map(|last| now.duration_since(last).as_secs_f32())
+                        .unwrap_or(0.0);
+                    self.controller.update(world.camera_mut(), dt);
                     world.update_buffer(&web_gpu_context.queue);
                     web_gpu_context.draw(world);
-                }
-            }
-            WindowEvent::MouseWheel { delta, .. } => {
-                if let (MouseScrollDelta::PixelDelta(v), Some(world), Some(window)) =
-                    (delta, self.world.as_mut(), self.window.as_mut())
-                {
-                    world.move_obj(Matrix::<4>::translation(v.x as f32, -v.y as f32, 0.0));
-                    window.request_redraw();
-                }
-            }
-            WindowEvent::KeyboardInput {
-                event:
-                    KeyEvent {
-                        physical_key: PhysicalKey::Code(code),
-                        state,
-                        ..
-                    },
-                ..
-            } => {
-                if let (Some(window), Some(world)) = (self.window.as_ref(), self.world.as_mut()) {
-                    match (code, state) {
-                        (KeyCode::KeyW, ElementState::Released) => {
-                            world.rotate_obj(Matrix::<4>::rotate_x(-STEP));
-                            window.request_redraw();
-                        }
-                        (KeyCode::KeyS, ElementState::Released) => {
-                            world.rotate_obj(Matrix::<4>::rotate_x(STEP));
-                            window.request_redraw();
-                        }
-                        (KeyCode::KeyA, ElementState::Released) => {
-                            world.rotate_obj(Matrix::<4>::rotate_y(-STEP));
-                            window.request_redraw();
-                        }
-                        (KeyCode::KeyD, ElementState::Released) => {
-                            world.rotate_obj(Matrix::<4>::rotate_y(STEP));
-                            window.request_redraw();
-                        }
-                        _ => {}
+
+                    // keep drawing while keys are held down
+                    if self.controller.is_active() {
+                        self.last_frame = Some(now);
+                        window.request_redraw();
+                    } else {
+                        self.last_frame = None;
                     }
                 }
             }
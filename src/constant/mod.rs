@@ -1,5 +1,7 @@
 // ACCURACY
 pub const EPSILON: f32 = 0.000001;
+// tolerance of a point on a shape surface
+pub const SURFACE_EPSILON: f32 = 0.0001;
 pub const TICK: f32 = 8_f32; //8ms
 
 // PHYSICS CONSTANT
@@ -7,3 +9,12 @@ pub const GRAVITY: f32 = 9.807 / 1000_f32 / 1000_f32; //9,807 m/s²
 
 // tracing
 pub const REFLECTION_LIMIT: usize = 5;
+// bounces of a path before it is cut off
+pub const PATH_DEPTH_LIMIT: usize = 16;
+// bounces of a path before russian roulette may end it
+pub const ROULETTE_DEPTH: usize = 3;
+// move hit point off the surface against self shadowing
+pub const OVER_POINT_OFFSET: f32 = 0.001;
+
+// rendering
+pub const MAX_LIGHT_COUNT: usize = 16;
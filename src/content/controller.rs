@@ -0,0 +1,279 @@
+use std::collections::HashSet;
+
+use winit::{
+    event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent},
+    keyboard::{KeyCode, PhysicalKey},
+};
+
+use crate::{
+    content::scene::Camera,
+    math::algebra::{
+        common::{Dimension4, deg_to_rad},
+        vector::Vector,
+    },
+};
+
+// degree per pixel of mouse movement
+const ROTATE_SENSITIVITY: f32 = 0.3;
+// distance ratio per scroll line
+const ZOOM_SENSITIVITY: f32 = 0.1;
+// world unit per second
+const FLY_SPEED: f32 = 1000.0;
+// pixel per scroll line for touch pads sending PixelDelta
+const PIXEL_PER_LINE: f32 = 20.0;
+const MIN_DISTANCE: f32 = 1.0;
+const MAX_PITCH: f32 = 89.0;
+// keys moving the camera in free fly mode
+const MOVEMENT_KEYS: [KeyCode; 8] = [
+    KeyCode::KeyW,
+    KeyCode::KeyS,
+    KeyCode::KeyD,
+    KeyCode::KeyA,
+    KeyCode::KeyE,
+    KeyCode::Space,
+    KeyCode::KeyQ,
+    KeyCode::ShiftLeft,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlMode {
+    // drag to rotate around the target, wheel to zoom
+    Orbit,
+    // WASD to move, drag to look around
+    FreeFly,
+}
+
+pub struct CameraController {
+    mode: ControlMode,
+    pressed_keys: HashSet<KeyCode>,
+    dragging: bool,
+    cursor: Option<(f64, f64)>,
+    // accumulated since last update
+    mouse_delta: (f32, f32),
+    scroll_delta: f32,
+}
+
+impl CameraController {
+    pub fn new(mode: ControlMode) -> Self {
+        Self {
+            mode,
+            pressed_keys: HashSet::new(),
+            dragging: false,
+            cursor: None,
+            mouse_delta: (0.0, 0.0),
+            scroll_delta: 0.0,
+        }
+    }
+
+    pub fn mode(&self) -> ControlMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: ControlMode) {
+        self.mode = mode;
+    }
+
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            ControlMode::Orbit => ControlMode::FreeFly,
+            ControlMode::FreeFly => ControlMode::Orbit,
+        };
+    }
+
+    /// true if the camera keeps moving without new events, i.e. movement keys
+    /// held down in free fly mode
+    pub fn is_active(&self) -> bool {
+        self.mode == ControlMode::FreeFly
+            && self
+                .pressed_keys
+                .iter()
+                .any(|code| MOVEMENT_KEYS.contains(code))
+    }
+
+    /// returns true if the event changes the camera
+    pub fn process_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(code),
+                        state,
+                        repeat,
+                        ..
+                    },
+                ..
+            } => match (code, state) {
+                (KeyCode::Tab, ElementState::Pressed) => {
+                    if !repeat {
+                        self.toggle_mode();
+                    }
+                    false
+                }
+                (_, ElementState::Pressed) => self.pressed_keys.insert(*code),
+                (_, ElementState::Released) => self.pressed_keys.remove(code),
+            },
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.dragging = *state == ElementState::Pressed;
+                false
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let moved = match (self.dragging, self.cursor) {
+                    (true, Some((x, y))) => {
+                        self.mouse_delta.0 += (position.x - x) as f32;
+                        self.mouse_delta.1 += (position.y - y) as f32;
+                        true
+                    }
+                    _ => false,
+                };
+                self.cursor = Some((position.x, position.y));
+                moved
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.cursor = None;
+                false
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.scroll_delta += match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(p) => p.y as f32 / PIXEL_PER_LINE,
+                };
+                true
+            }
+            WindowEvent::Focused(false) => {
+                self.pressed_keys.clear();
+                self.dragging = false;
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// apply the input gathered since last update, dt in second
+    pub fn update(&mut self, camera: &mut Camera, dt: f32) {
+        match self.mode {
+            ControlMode::Orbit => self.update_orbit(camera),
+            ControlMode::FreeFly => self.update_free_fly(camera, dt),
+        }
+        self.mouse_delta = (0.0, 0.0);
+        self.scroll_delta = 0.0;
+    }
+
+    fn update_orbit(&self, camera: &mut Camera) {
+        let offset = Vector::from_points(&camera.target, &camera.position);
+        let (yaw, pitch) = yaw_pitch(&offset);
+        let yaw = yaw - deg_to_rad(self.mouse_delta.0 * ROTATE_SENSITIVITY);
+        let pitch = clamp_pitch(pitch + deg_to_rad(self.mouse_delta.1 * ROTATE_SENSITIVITY));
+        let distance =
+            (offset.norm() * (1.0 - self.scroll_delta * ZOOM_SENSITIVITY)).max(MIN_DISTANCE);
+        camera.position = camera.target + from_yaw_pitch(yaw, pitch) * distance;
+    }
+
+    fn update_free_fly(&self, camera: &mut Camera, dt: f32) {
+        let (yaw, pitch) = yaw_pitch(&camera.direction());
+        let yaw = yaw - deg_to_rad(self.mouse_delta.0 * ROTATE_SENSITIVITY);
+        let pitch = clamp_pitch(pitch - deg_to_rad(self.mouse_delta.1 * ROTATE_SENSITIVITY));
+        let forward = from_yaw_pitch(yaw, pitch);
+        let right = forward.cross(&camera.up).unit().unwrap_or(Vector::unit_x());
+
+        let mut movement = Vector::zero();
+        self.pressed_keys.iter().for_each(|code| match code {
+            KeyCode::KeyW => movement.translate_by(&forward),
+            KeyCode::KeyS => movement.translate_by(&-forward),
+            KeyCode::KeyD => movement.translate_by(&right),
+            KeyCode::KeyA => movement.translate_by(&-right),
+            KeyCode::KeyE | KeyCode::Space => movement.translate_by(&camera.up),
+            KeyCode::KeyQ | KeyCode::ShiftLeft => movement.translate_by(&-camera.up),
+            _ => {}
+        });
+        if let Ok(unit) = movement.unit() {
+            camera.position = camera.position + unit * (FLY_SPEED * dt);
+        }
+        camera.look_to(forward);
+    }
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        Self::new(ControlMode::Orbit)
+    }
+}
+
+// yaw around y axis from positive z, pitch up from xz plane, in radius
+fn yaw_pitch(v: &Vector) -> (f32, f32) {
+    let unit = v.unit().unwrap_or(Vector::unit_z());
+    (
+        unit.get_x().atan2(unit.get_z()),
+        unit.get_y().clamp(-1.0, 1.0).asin(),
+    )
+}
+
+fn from_yaw_pitch(yaw: f32, pitch: f32) -> Vector {
+    Vector::vector(
+        pitch.cos() * yaw.sin(),
+        pitch.sin(),
+        pitch.cos() * yaw.cos(),
+    )
+}
+
+fn clamp_pitch(pitch: f32) -> f32 {
+    let max = deg_to_rad(MAX_PITCH);
+    pitch.clamp(-max, max)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        content::{
+            controller::{CameraController, ControlMode},
+            scene::Camera,
+        },
+        math::algebra::{common::FuzzyEq, point::Point, vector::Vector},
+    };
+
+    #[test]
+    fn orbit_and_fly() {
+        let mut camera = Camera::new(
+            Point::point(0.0, 0.0, 10.0),
+            Point::origin(),
+            Vector::unit_y(),
+            60.0,
+            1.0,
+            0.1,
+            100.0,
+        );
+        let mut controller = CameraController::new(ControlMode::Orbit);
+        controller.mouse_delta = (100.0, 50.0);
+        controller.update(&mut camera, 0.016);
+        // orbit keeps the distance to target
+        assert!(camera.position.distance(&camera.target).fuzzy_eq(&10.0));
+
+        // keys do nothing in orbit mode
+        controller
+            .pressed_keys
+            .insert(winit::keyboard::KeyCode::KeyW);
+        assert!(!controller.is_active());
+
+        controller.set_mode(ControlMode::FreeFly);
+        assert!(controller.is_active());
+        let direction = camera.direction();
+        let position = camera.position;
+        controller.update(&mut camera, 0.001);
+        assert!(
+            Vector::from_points(&position, &camera.position)
+                .unit()
+                .unwrap()
+                .fuzzy_eq(&direction)
+        );
+
+        // unrelated keys do not move the camera
+        controller.pressed_keys.clear();
+        controller
+            .pressed_keys
+            .insert(winit::keyboard::KeyCode::KeyP);
+        assert!(!controller.is_active());
+    }
+}
@@ -0,0 +1,151 @@
+/// refers to an object in a `HandleMap`, a handle of a removed object never
+/// matches the object later stored in the same slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectHandle {
+    index: u32,
+    generation: u32,
+}
+
+impl ObjectHandle {
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+/// slot storage with generational handles, iterates in insertion order
+pub struct HandleMap<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+    order: Vec<ObjectHandle>,
+}
+
+impl<T> HandleMap<T> {
+    pub fn new() -> Self {
+        Self {
+            slots: vec![],
+            free: vec![],
+            order: vec![],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    pub fn insert(&mut self, value: T) -> ObjectHandle {
+        let handle = match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.value = Some(value);
+                ObjectHandle {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    value: Some(value),
+                });
+                ObjectHandle {
+                    index: self.slots.len() as u32 - 1,
+                    generation: 0,
+                }
+            }
+        };
+        self.order.push(handle);
+        handle
+    }
+
+    pub fn remove(&mut self, handle: ObjectHandle) -> Option<T> {
+        let slot = self.slots.get_mut(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+        self.order.retain(|item| *item != handle);
+        Some(value)
+    }
+
+    pub fn contains(&self, handle: ObjectHandle) -> bool {
+        self.get(handle).is_some()
+    }
+
+    pub fn get(&self, handle: ObjectHandle) -> Option<&T> {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.value.as_ref())
+    }
+
+    pub fn get_mut(&mut self, handle: ObjectHandle) -> Option<&mut T> {
+        self.slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.value.as_mut())
+    }
+
+    /// handles in insertion order
+    pub fn handles(&self) -> &[ObjectHandle] {
+        &self.order
+    }
+
+    /// in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = (ObjectHandle, &T)> {
+        self.order
+            .iter()
+            .filter_map(|handle| self.get(*handle).map(|value| (*handle, value)))
+    }
+
+    /// in insertion order
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        let order = &self.order;
+        let mut values: Vec<Option<&mut T>> = self
+            .slots
+            .iter_mut()
+            .map(|slot| slot.value.as_mut())
+            .collect();
+        order
+            .iter()
+            .filter_map(move |handle| values[handle.index as usize].take())
+    }
+}
+
+impl<T> Default for HandleMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn test_stale_handle() {
+    let mut map = HandleMap::new();
+    let first = map.insert("first");
+    let second = map.insert("second");
+    assert_eq!(map.remove(first), Some("first"));
+    // slot is reused with a new generation
+    let third = map.insert("third");
+    assert_eq!(first.index(), third.index());
+    assert_eq!(map.get(first), None);
+    assert_eq!(map.get(third), Some(&"third"));
+    assert_eq!(
+        map.iter().map(|(_, value)| *value).collect::<Vec<_>>(),
+        vec!["second", "third"]
+    );
+    assert_eq!(map.handles(), &[second, third]);
+}
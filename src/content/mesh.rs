@@ -0,0 +1,52 @@
+use crate::{
+    content::Vertex,
+    math::{
+        algebra::{point::Point, vector::Vector},
+        geometry::triangle_mesh::TriangleMesh,
+    },
+    physics::color::Color,
+};
+
+/// vertices shared by triangles, every 3 indices make a triangle
+pub struct Mesh {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    pub fn new(vertices: Vec<Vertex>, indices: Vec<u32>) -> Self {
+        Self { vertices, indices }
+    }
+
+    /// every 3 vertices make a triangle, nothing shared
+    pub fn from_vertices(vertices: Vec<Vertex>) -> Self {
+        let indices = (0..vertices.len() as u32).collect();
+        Self { vertices, indices }
+    }
+
+    /// all vertices in the same color
+    pub fn from_obj(model: &obj::Obj, color: Color) -> Self {
+        let vertices = model
+            .vertices
+            .iter()
+            .map(|vertex| Vertex {
+                position: Point::point(vertex.position[0], vertex.position[1], vertex.position[2]),
+                color,
+                normal: Vector::vector(vertex.normal[0], vertex.normal[1], vertex.normal[2]),
+            })
+            .collect();
+        let indices = model.indices.iter().map(|&i| i as u32).collect();
+        Self::new(vertices, indices)
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// smooth shaded mesh for the CPU tracer, in the same object space
+    pub fn to_triangle_mesh(&self) -> Result<TriangleMesh, String> {
+        let positions: Vec<Point> = self.vertices.iter().map(|vertex| vertex.position).collect();
+        let normals: Vec<Vector> = self.vertices.iter().map(|vertex| vertex.normal).collect();
+        TriangleMesh::new(&positions, &normals, &self.indices)
+    }
+}
@@ -5,8 +5,13 @@ use crate::{
     physics::color::Color,
 };
 
+pub mod controller;
+pub mod handle;
+pub mod mesh;
 pub mod model_object;
 pub mod scene;
+pub mod scene_graph;
+pub mod transform;
 pub mod world;
 
 pub trait WithGPUBuffer {
@@ -8,49 +8,116 @@ use wgpu::{
 };
 
 use crate::{
-    content::{Vertex, WithGPUBuffer},
-    math::algebra::{matrix::Matrix, point::Point, vector::Vector},
-    physics::color::Color,
+    content::{Vertex, WithGPUBuffer, mesh::Mesh, transform::Transform},
+    math::{
+        algebra::{matrix::Matrix, point::Point, quaternion::Quaternion, vector::Vector},
+        geometry::{transformed::Transformed, triangle_mesh::TriangleMesh},
+    },
+    physics::{color::Color, material::Material, phong::Phong},
 };
 
 pub struct ModelObject {
-    pub vertex_data: Vec<Vertex>,
+    pub mesh: Mesh,
     pub vertex_buffer: Option<Buffer>,
-    pub transform: [Matrix<4>; 3],
+    pub index_buffer: Option<Buffer>,
+    // relative to parent
+    pub transform: Transform,
+    // model and normal matrix with all ancestors applied
+    world_matrix: Matrix<4>,
+    world_normal: Matrix<4>,
+    world_dirty: bool,
     pub transform_buffer: Option<Buffer>,
     pub transform_bind_group: Option<BindGroup>,
+    material: Material,
+    material_dirty: bool,
+    pub material_buffer: Option<Buffer>,
+    pub material_bind_group: Option<BindGroup>,
 }
 
 impl ModelObject {
-    pub fn new(
-        vertex_data: Vec<Vertex>,
-        scale: Matrix<4>,
-        rotation: Matrix<4>,
-        translation: Matrix<4>,
-    ) -> Self {
+    pub fn new(mesh: Mesh, transform: Transform) -> Self {
         Self {
-            vertex_data,
+            mesh,
             vertex_buffer: None,
-            transform: [scale, rotation, translation],
+            index_buffer: None,
+            world_matrix: transform.model_matrix(),
+            world_normal: transform.normal_matrix(),
+            world_dirty: true,
+            transform,
             transform_buffer: None,
             transform_bind_group: None,
+            material: Material::default(),
+            material_dirty: true,
+            material_buffer: None,
+            material_bind_group: None,
         }
     }
+
+    pub fn with_material(mut self, material: impl Into<Material>) -> Self {
+        self.set_material(material);
+        self
+    }
+
+    pub fn get_material(&self) -> &Material {
+        &self.material
+    }
+
+    pub fn set_material(&mut self, material: impl Into<Material>) {
+        self.material = material.into();
+        self.material_dirty = true;
+    }
+
+    pub fn world_matrix(&self) -> Matrix<4> {
+        self.world_matrix
+    }
+
+    /// apply parent world matrix, None for root objects,
+    /// returns true if the world matrix changed
+    pub fn update_world(&mut self, parent: Option<&Matrix<4>>, parent_changed: bool) -> bool {
+        let changed = parent_changed || self.transform.is_dirty();
+        if changed {
+            self.world_matrix = match parent {
+                Some(parent) => *parent * self.transform.model_matrix(),
+                None => self.transform.model_matrix(),
+            };
+            self.world_normal = self.world_matrix.normal_matrix();
+            self.world_dirty = true;
+            self.transform.mark_clean();
+        }
+        changed
+    }
+
+    /// smooth shaded mesh placed by the world matrix, for the CPU tracer
+    pub fn to_trace_shape(&self) -> Result<Transformed<TriangleMesh>, String> {
+        Transformed::new(self.mesh.to_triangle_mesh()?, self.world_matrix)
+    }
+
+    // (model, normal)
+    fn transform_raw(&self) -> [[[f32; 4]; 4]; 2] {
+        [self.world_matrix.get_raw(), self.world_normal.get_raw()]
+    }
 }
 
 impl WithGPUBuffer for ModelObject {
     fn init_buffer(&mut self, device: &Device, bind_group_layout: &[BindGroupLayout]) {
         self.vertex_buffer = Some(device.create_buffer_init(&BufferInitDescriptor {
             label: None,
-            contents: cast_slice(&self.vertex_data),
+            contents: cast_slice(&self.mesh.vertices),
             usage: BufferUsages::VERTEX,
         }));
 
+        self.index_buffer = Some(device.create_buffer_init(&BufferInitDescriptor {
+            label: None,
+            contents: cast_slice(&self.mesh.indices),
+            usage: BufferUsages::INDEX,
+        }));
+
         self.transform_buffer = Some(device.create_buffer_init(&BufferInitDescriptor {
             label: None,
-            contents: cast_slice(&self.transform),
+            contents: cast_slice(&self.transform_raw()),
             usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
         }));
+        self.world_dirty = false;
 
         self.transform_bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: None,
@@ -60,24 +127,52 @@ impl WithGPUBuffer for ModelObject {
                 resource: self.transform_buffer.as_ref().unwrap().as_entire_binding(),
             }],
         }));
+
+        self.material_buffer = Some(device.create_buffer_init(&BufferInitDescriptor {
+            label: None,
+            contents: cast_slice(&self.material.get_raw()),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        }));
+        self.material_dirty = false;
+
+        self.material_bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &bind_group_layout[1],
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: self.material_buffer.as_ref().unwrap().as_entire_binding(),
+            }],
+        }));
     }
 
     fn update_buffer(&mut self, queue: &Queue) {
-        queue.write_buffer(
-            self.transform_buffer.as_ref().unwrap(),
-            0,
-            cast_slice(&self.transform),
-        );
+        // only write objects changed since last upload
+        if self.world_dirty {
+            queue.write_buffer(
+                self.transform_buffer.as_ref().unwrap(),
+                0,
+                cast_slice(&self.transform_raw()),
+            );
+            self.world_dirty = false;
+        }
+        if self.material_dirty {
+            queue.write_buffer(
+                self.material_buffer.as_ref().unwrap(),
+                0,
+                cast_slice(&self.material.get_raw()),
+            );
+            self.material_dirty = false;
+        }
     }
 }
 
 impl ModelObject {
-    pub fn move_obj(&mut self, translation: Matrix<4>) {
-        self.transform[2] = translation * self.transform[2];
+    pub fn move_obj(&mut self, translation: Vector) {
+        self.transform.translate(&translation);
     }
 
-    pub fn rotate_obj(&mut self, rotation: Matrix<4>) {
-        self.transform[1] = rotation * self.transform[1];
+    pub fn rotate_obj(&mut self, rotation: Quaternion) {
+        self.transform.rotate(rotation);
     }
 }
 
@@ -91,16 +186,6 @@ pub fn generate_teapot() -> ModelObject {
     const PATH: &str = "src/content/asset/teapot.obj";
 
     let model = load_obj_model(PATH).unwrap();
-    let mut vertex_data = vec![];
-    for i in model.indices {
-        let position = model.vertices[i as usize].position;
-        let normal = model.vertices[i as usize].normal;
-        vertex_data.push(Vertex {
-            position: Point::point(position[0], position[1], position[2]),
-            color: Color::rgb(0.439, 0.329, 0.243),
-            normal: Vector::vector(normal[0], normal[1], normal[2]),
-        });
-    }
 
     // position info
     let scale: [f32; 3] = [100.0, 100.0, 100.0];
@@ -108,17 +193,18 @@ pub fn generate_teapot() -> ModelObject {
     let position: [f32; 3] = [0.0, -100.0, -1000.0];
 
     ModelObject::new(
-        vertex_data,
-        Matrix::<4>::scale(scale[0], scale[1], scale[2]),
-        Matrix::<4>::rotate_z(rotation[2])
-            * Matrix::<4>::rotate_y(rotation[1])
-            * Matrix::<4>::rotate_x(rotation[0]),
-        Matrix::<4>::translation(position[0], position[1], position[2]),
+        Mesh::from_obj(&model, Color::rgb(0.439, 0.329, 0.243)),
+        Transform::new(
+            Vector::vector(position[0], position[1], position[2]),
+            Quaternion::from_euler(rotation[0], rotation[1], rotation[2]),
+            Vector::vector(scale[0], scale[1], scale[2]),
+        ),
     )
+    .with_material(Phong::default().with_shininess(512.0))
 }
 
 pub fn generate_ground() -> ModelObject {
-    let vertex_data: Vec<Vertex> = vec![
+    let vertices: Vec<Vertex> = vec![
         Vertex {
             position: Point::point(5000.0, 0.0, 5000.0),
             color: Color::rgb(1.0, 1.0, 1.0),
@@ -139,17 +225,8 @@ pub fn generate_ground() -> ModelObject {
             color: Color::rgb(1.0, 1.0, 1.0),
             normal: Vector::unit_y(),
         },
-        Vertex {
-            position: Point::point(-5000.0, 0.0, 5000.0),
-            color: Color::rgb(1.0, 1.0, 1.0),
-            normal: Vector::unit_y(),
-        },
-        Vertex {
-            position: Point::point(5000.0, 0.0, -5000.0),
-            color: Color::rgb(1.0, 1.0, 1.0),
-            normal: Vector::unit_y(),
-        },
     ];
+    let indices: Vec<u32> = vec![0, 1, 2, 3, 2, 1];
 
     // position info
     let scale: [f32; 3] = [1.0, 1.0, 1.0];
@@ -157,11 +234,36 @@ pub fn generate_ground() -> ModelObject {
     let position: [f32; 3] = [0.0, -1000.0, -1000.0];
 
     ModelObject::new(
-        vertex_data,
-        Matrix::<4>::scale(scale[0], scale[1], scale[2]),
-        Matrix::<4>::rotate_z(rotation[2])
-            * Matrix::<4>::rotate_y(rotation[1])
-            * Matrix::<4>::rotate_x(rotation[0]),
-        Matrix::<4>::translation(position[0], position[1], position[2]),
+        Mesh::new(vertices, indices),
+        Transform::new(
+            Vector::vector(position[0], position[1], position[2]),
+            Quaternion::from_euler(rotation[0], rotation[1], rotation[2]),
+            Vector::vector(scale[0], scale[1], scale[2]),
+        ),
     )
+    .with_material(Phong::default().with_specular(0.0))
+}
+
+#[test]
+fn teapot_share_vertices() {
+    let teapot = generate_teapot();
+    let vertex_count = teapot.mesh.vertices.len() as u32;
+    assert!(teapot.mesh.vertices.len() < teapot.mesh.indices.len());
+    assert!(teapot.mesh.indices.iter().all(|&i| i < vertex_count));
+}
+
+#[test]
+fn teapot_trace_shape() {
+    use crate::math::geometry::{common::Intersect, ray::Ray};
+
+    let teapot = generate_teapot();
+    let shape = teapot.to_trace_shape().unwrap();
+    assert_eq!(
+        shape.get_shape().triangle_count(),
+        teapot.mesh.triangle_count()
+    );
+    // from the camera to the teapot in front
+    let ray = Ray::new(Point::origin(), -Vector::unit_z()).unwrap();
+    let hit = shape.intersect(&ray)[0];
+    assert!(hit.get_t() > 0.0 && hit.is_front_face());
 }
@@ -6,106 +6,277 @@ use wgpu::{
 use winit::dpi::PhysicalSize;
 
 use crate::{
+    constant::MAX_LIGHT_COUNT,
     content::WithGPUBuffer,
-    math::algebra::{common::Dimension4, matrix::Matrix, point::Point, vector::Vector},
+    math::{
+        algebra::{
+            common::{Dimension4, deg_to_rad},
+            matrix::Matrix,
+            point::Point,
+            vector::Vector,
+        },
+        geometry::ray::Ray,
+    },
+    physics::{
+        color::Color,
+        light::{DirectionalLight, Light, SpotLight},
+    },
 };
 
+/// perspective camera, looking from position to target
+#[derive(Debug, Clone, Copy)]
+pub struct Camera {
+    pub position: Point,
+    pub target: Point,
+    pub up: Vector,
+    // vertical field of view in degree
+    pub fov_y: f32,
+    pub aspect: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Camera {
+    pub fn new(
+        position: Point,
+        target: Point,
+        up: Vector,
+        fov_y: f32,
+        aspect: f32,
+        near: f32,
+        far: f32,
+    ) -> Self {
+        Self {
+            position,
+            target,
+            up,
+            fov_y,
+            aspect,
+            near,
+            far,
+        }
+    }
+
+    /// unit vector from position to target
+    pub fn direction(&self) -> Vector {
+        Vector::from_points(&self.position, &self.target)
+            .unit()
+            .unwrap_or(-Vector::unit_z())
+    }
+
+    /// keep the distance to target, look to the given direction
+    pub fn look_to(&mut self, direction: Vector) {
+        let distance = self.position.distance(&self.target).max(1.0);
+        if let Ok(unit) = direction.unit() {
+            self.target = self.position + unit * distance;
+        }
+    }
+
+    pub fn view_matrix(&self) -> Matrix<4> {
+        Matrix::look_at(self.position, self.target, self.up).unwrap_or(Matrix::translation(
+            -self.position.get_x(),
+            -self.position.get_y(),
+            -self.position.get_z(),
+        ))
+    }
+
+    pub fn projection_matrix(&self) -> Matrix<4> {
+        Matrix::perspective(self.fov_y, self.aspect, self.near, self.far)
+    }
+
+    /// ray through the center of pixel (x, y) of an image, y goes down
+    pub fn ray_for_pixel(&self, x: u32, y: u32, width: u32, height: u32) -> Result<Ray, String> {
+        self.ray_through(x as f32 + 0.5, y as f32 + 0.5, width, height)
+    }
+
+    /// ray through a position on an image in pixel units, (0, 0) is the top left
+    /// corner of the first pixel
+    pub fn ray_through(&self, x: f32, y: f32, width: u32, height: u32) -> Result<Ray, String> {
+        let forward = Vector::from_points(&self.position, &self.target).unit()?;
+        let right = forward.cross(&self.up).unit()?;
+        let up = right.cross(&forward);
+        let half_height = (deg_to_rad(self.fov_y) / 2.0).tan();
+        let half_width = half_height * width.max(1) as f32 / height.max(1) as f32;
+        let ndc_x = 2.0 * x / width.max(1) as f32 - 1.0;
+        let ndc_y = 1.0 - 2.0 * y / height.max(1) as f32;
+        Ray::new(
+            self.position,
+            forward + right * (ndc_x * half_width) + up * (ndc_y * half_height),
+        )
+    }
+
+    pub fn resize(&mut self, size: PhysicalSize<u32>) {
+        self.aspect = size.width.max(1) as f32 / size.height.max(1) as f32;
+    }
+}
+
 pub struct Scene {
     pub scene_buffer: Option<Buffer>,
+    pub light_buffer: Option<Buffer>,
     pub scene_bind_group: Option<BindGroup>,
 
-    // (width, height, near, far)
-    scene_config: Point,
-    light_position: Point,
-    light_direction: Vector,
-    eye_position: Point,
-    eye_direction: Vector,
+    pub camera: Camera,
+    lights: Vec<Light>,
 }
 
 impl Scene {
-    fn new(
-        scene_config: Point,
-        light_position: Point,
-        light_direction: Vector,
-        eye_position: Point,
-        eye_direction: Vector,
-    ) -> Self {
+    fn new(camera: Camera) -> Self {
         Self {
-            scene_config,
-            light_position,
-            light_direction,
-            eye_position,
-            eye_direction,
+            camera,
+            lights: vec![],
             scene_bind_group: None,
             scene_buffer: None,
+            light_buffer: None,
         }
     }
+
     pub fn resize(&mut self, size: PhysicalSize<u32>) {
-        self.scene_config.set_x(size.width as f32);
-        self.scene_config.set_y(size.height as f32);
+        self.camera.resize(size);
+    }
+
+    /// at most `MAX_LIGHT_COUNT` lights
+    pub fn add_light(&mut self, light: Light) -> Result<usize, String> {
+        if self.lights.len() >= MAX_LIGHT_COUNT {
+            return Err(format!("at most {MAX_LIGHT_COUNT} lights"));
+        }
+        self.lights.push(light);
+        Ok(self.lights.len() - 1)
+    }
+
+    pub fn remove_light(&mut self, index: usize) -> Option<Light> {
+        (index < self.lights.len()).then(|| self.lights.remove(index))
+    }
+
+    pub fn lights(&self) -> &[Light] {
+        &self.lights
+    }
+
+    pub fn lights_mut(&mut self) -> &mut [Light] {
+        &mut self.lights
+    }
+
+    // (view, projection, eye_position, eye_direction) as f32,
+    // then (light_count, 0, 0, 0) as u32
+    fn scene_data(&self) -> Vec<u8> {
+        let mut data = vec![];
+        data.extend(self.camera.view_matrix().get_raw());
+        data.extend(self.camera.projection_matrix().get_raw());
+        data.push(self.camera.position.get_raw());
+        data.push(self.camera.direction().get_raw());
+        let mut bytes = cast_slice::<[f32; 4], u8>(&data).to_vec();
+        bytes.extend_from_slice(cast_slice(&[self.lights.len() as u32, 0, 0, 0]));
+        bytes
+    }
+
+    // always MAX_LIGHT_COUNT lights, unused ones are zero
+    fn light_data(&self) -> Vec<[[f32; 4]; 5]> {
+        let mut data: Vec<[[f32; 4]; 5]> =
+            self.lights.iter().map(|light| light.get_raw()).collect();
+        data.resize(MAX_LIGHT_COUNT, [[0.0; 4]; 5]);
+        data
     }
 }
 
 impl WithGPUBuffer for Scene {
     fn init_buffer(&mut self, device: &Device, bind_group_layout: &[BindGroupLayout]) {
-        self.scene_buffer = Some(
-            device.create_buffer_init(&BufferInitDescriptor {
-                label: None,
-                contents: cast_slice(&[
-                    Matrix::perspective(self.scene_config, self.eye_position, self.eye_direction)
-                        .get_raw(),
-                    [
-                        self.light_position.get_raw(),
-                        self.light_direction.get_raw(),
-                        self.eye_position.get_raw(),
-                        self.eye_direction.get_raw(),
-                    ],
-                ]),
-                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
-            }),
-        );
+        self.scene_buffer = Some(device.create_buffer_init(&BufferInitDescriptor {
+            label: None,
+            contents: &self.scene_data(),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        }));
+
+        self.light_buffer = Some(device.create_buffer_init(&BufferInitDescriptor {
+            label: None,
+            contents: cast_slice(&self.light_data()),
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+        }));
 
         self.scene_bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: None,
             layout: &bind_group_layout[0],
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: self.scene_buffer.as_ref().unwrap().as_entire_binding(),
-            }],
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.scene_buffer.as_ref().unwrap().as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.light_buffer.as_ref().unwrap().as_entire_binding(),
+                },
+            ],
         }));
     }
 
     fn update_buffer(&mut self, queue: &Queue) {
+        queue.write_buffer(self.scene_buffer.as_ref().unwrap(), 0, &self.scene_data());
         queue.write_buffer(
-            self.scene_buffer.as_ref().unwrap(),
+            self.light_buffer.as_ref().unwrap(),
             0,
-            cast_slice(&[
-                Matrix::perspective(self.scene_config, self.eye_position, self.eye_direction)
-                    .get_raw(),
-                [
-                    self.light_position.get_raw(),
-                    self.light_direction.get_raw(),
-                    self.eye_position.get_raw(),
-                    self.eye_direction.get_raw(),
-                ],
-            ]),
+            cast_slice(&self.light_data()),
         );
     }
 }
 
 pub fn generate_scene(size: PhysicalSize<u32>) -> Scene {
     // perspective
+    let fov_y: f32 = 60.0;
     let near: f32 = 500.0;
     let far: f32 = 200000.0;
     // light
     let light_position: [f32; 3] = [0.0, 1000.0, -100.0];
     let light_direction: [f32; 3] = [0.0, 0.0, -1.0];
+    let sun_direction: [f32; 3] = [1.0, -2.0, -1.0];
 
-    Scene::new(
-        Point::new(size.width as f32, size.height as f32, near, far),
-        Point::point(light_position[0], light_position[1], light_position[2]),
-        Vector::vector(light_direction[0], light_direction[1], light_direction[2]),
+    let mut camera = Camera::new(
         Point::origin(),
-        -Vector::unit_z(), //only support in negative z direction
-    )
+        Point::point(0.0, 0.0, -1000.0),
+        Vector::unit_y(),
+        fov_y,
+        1.0,
+        near,
+        far,
+    );
+    camera.resize(size);
+
+    let mut scene = Scene::new(camera);
+    scene
+        .add_light(Light::Spot(SpotLight::new(
+            Point::point(light_position[0], light_position[1], light_position[2]),
+            Vector::vector(light_direction[0], light_direction[1], light_direction[2]),
+            Color::white(),
+            50.0,
+            60.0,
+        )))
+        .unwrap();
+    scene
+        .add_light(Light::Directional(DirectionalLight::new(
+            Vector::vector(sun_direction[0], sun_direction[1], sun_direction[2]),
+            Color::white() * 0.3,
+        )))
+        .unwrap();
+    scene
+}
+
+#[test]
+fn light_limit() {
+    use crate::physics::light::PointLight;
+
+    let mut scene = generate_scene(PhysicalSize::new(64, 48));
+    while scene.lights().len() < MAX_LIGHT_COUNT {
+        scene
+            .add_light(Light::Point(PointLight::default()))
+            .unwrap();
+    }
+    assert!(
+        scene
+            .add_light(Light::Point(PointLight::default()))
+            .is_err()
+    );
+    assert_eq!(scene.light_data().len(), MAX_LIGHT_COUNT);
+
+    // light count as u32 after the matrices and eye vectors
+    let data = scene.scene_data();
+    assert_eq!(data.len(), (16 * 2 + 4 * 3) * 4);
+    let count: &[u32] = cast_slice(&data[data.len() - 16..]);
+    assert_eq!(count, &[MAX_LIGHT_COUNT as u32, 0, 0, 0]);
 }
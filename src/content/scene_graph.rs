@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use crate::content::handle::ObjectHandle;
+
+#[derive(Debug, Default, Clone)]
+pub struct SceneNode {
+    pub parent: Option<ObjectHandle>,
+    pub children: Vec<ObjectHandle>,
+}
+
+/// parent-child relation between objects, by object handle
+#[derive(Debug, Default)]
+pub struct SceneGraph {
+    nodes: HashMap<ObjectHandle, SceneNode>,
+    // nodes without parent, in insertion order
+    roots: Vec<ObjectHandle>,
+}
+
+impl SceneGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(&self, id: ObjectHandle) -> bool {
+        self.nodes.contains_key(&id)
+    }
+
+    pub fn insert(&mut self, id: ObjectHandle, parent: Option<ObjectHandle>) -> Result<(), String> {
+        if self.contains(id) {
+            return Err("node already exists".to_string());
+        }
+        if let Some(parent) = parent
+            && !self.contains(parent)
+        {
+            return Err("parent not found".to_string());
+        }
+        self.nodes.insert(id, SceneNode::default());
+        self.attach(id, parent);
+        Ok(())
+    }
+
+    /// remove the node, its children become roots
+    pub fn remove(&mut self, id: ObjectHandle) -> Option<SceneNode> {
+        self.detach(id);
+        let node = self.nodes.remove(&id)?;
+        node.children.iter().for_each(|child| {
+            self.attach(*child, None);
+        });
+        Some(node)
+    }
+
+    pub fn parent(&self, id: ObjectHandle) -> Option<ObjectHandle> {
+        self.nodes.get(&id).and_then(|node| node.parent)
+    }
+
+    pub fn children(&self, id: ObjectHandle) -> &[ObjectHandle] {
+        self.nodes
+            .get(&id)
+            .map(|node| node.children.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn roots(&self) -> &[ObjectHandle] {
+        &self.roots
+    }
+
+    /// true if ancestor is the node itself or one of its ancestors
+    pub fn is_ancestor(&self, ancestor: ObjectHandle, id: ObjectHandle) -> bool {
+        let mut current = Some(id);
+        while let Some(node) = current {
+            if node == ancestor {
+                return true;
+            }
+            current = self.parent(node);
+        }
+        false
+    }
+
+    /// move the node under a new parent, or to the roots with None
+    pub fn set_parent(
+        &mut self,
+        id: ObjectHandle,
+        parent: Option<ObjectHandle>,
+    ) -> Result<(), String> {
+        if !self.contains(id) {
+            return Err("node not found".to_string());
+        }
+        if let Some(parent) = parent {
+            if !self.contains(parent) {
+                return Err("parent not found".to_string());
+            }
+            if self.is_ancestor(id, parent) {
+                return Err("node can not be parented to its descendant".to_string());
+            }
+        }
+        self.detach(id);
+        self.attach(id, parent);
+        Ok(())
+    }
+
+    /// all node handles, parents before their children
+    pub fn traverse(&self) -> Vec<ObjectHandle> {
+        let mut order = vec![];
+        let mut stack: Vec<ObjectHandle> = self.roots.iter().rev().copied().collect();
+        while let Some(id) = stack.pop() {
+            order.push(id);
+            stack.extend(self.children(id).iter().rev());
+        }
+        order
+    }
+
+    fn attach(&mut self, id: ObjectHandle, parent: Option<ObjectHandle>) {
+        match parent {
+            Some(parent) => self.nodes.get_mut(&parent).unwrap().children.push(id),
+            None => self.roots.push(id),
+        }
+        self.nodes.get_mut(&id).unwrap().parent = parent;
+    }
+
+    fn detach(&mut self, id: ObjectHandle) {
+        match self.parent(id) {
+            Some(parent) => {
+                if let Some(node) = self.nodes.get_mut(&parent) {
+                    node.children.retain(|child| *child != id);
+                }
+            }
+            None => self.roots.retain(|root| *root != id),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use crate::math::algebra::{
+    common::Dimension4, matrix::Matrix, quaternion::Quaternion, vector::Vector,
+};
+
+/// scale first, then rotate, then translate
+#[derive(Debug, Clone, Copy)]
+pub struct Transform {
+    translation: Vector,
+    rotation: Quaternion,
+    scale: Vector,
+    // cached from translation, rotation and scale
+    model: Matrix<4>,
+    normal: Matrix<4>,
+    // changed since the world matrix was last updated
+    dirty: bool,
+}
+
+impl Transform {
+    pub fn new(translation: Vector, rotation: Quaternion, scale: Vector) -> Self {
+        let mut transform = Self {
+            translation,
+            rotation,
+            scale,
+            model: Matrix::identity(),
+            normal: Matrix::identity(),
+            dirty: true,
+        };
+        transform.update_matrix();
+        transform
+    }
+
+    pub fn get_translation(&self) -> Vector {
+        self.translation
+    }
+    pub fn get_rotation(&self) -> Quaternion {
+        self.rotation
+    }
+    pub fn get_scale(&self) -> Vector {
+        self.scale
+    }
+
+    pub fn set_translation(&mut self, translation: Vector) {
+        self.translation = translation;
+        self.update_matrix();
+    }
+    pub fn set_rotation(&mut self, rotation: Quaternion) {
+        self.rotation = rotation;
+        self.update_matrix();
+    }
+    pub fn set_scale(&mut self, scale: Vector) {
+        self.scale = scale;
+        self.update_matrix();
+    }
+
+    pub fn translate(&mut self, amount: &Vector) {
+        self.translation.translate_by(amount);
+        self.update_matrix();
+    }
+
+    pub fn rotate(&mut self, rotation: Quaternion) {
+        // normalize to keep rounding error from accumulating
+        self.rotation = (rotation * self.rotation).unit().unwrap_or(self.rotation);
+        self.update_matrix();
+    }
+
+    pub fn model_matrix(&self) -> Matrix<4> {
+        self.model
+    }
+
+    /// inverse transpose of the model matrix, keeps normals perpendicular
+    /// to the surface under non-uniform scale
+    pub fn normal_matrix(&self) -> Matrix<4> {
+        self.normal
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn mark_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    fn update_matrix(&mut self) {
+        let (x, y, z) = self.translation.get_value();
+        self.model = Matrix::<4>::translation(x, y, z)
+            * self.rotation.to_matrix()
+            * Matrix::<4>::scale(self.scale.get_x(), self.scale.get_y(), self.scale.get_z());
+        self.normal = self.model.normal_matrix();
+        self.dirty = true;
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::new(
+            Vector::zero(),
+            Quaternion::identity(),
+            Vector::vector(1.0, 1.0, 1.0),
+        )
+    }
+}
+
+#[test]
+fn test_normal_matrix() {
+    use crate::math::algebra::common::FuzzyEq;
+
+    let mut transform = Transform::new(
+        Vector::vector(1.0, 2.0, 3.0),
+        Quaternion::identity(),
+        Vector::vector(2.0, 1.0, 1.0),
+    );
+    transform.mark_clean();
+    transform.rotate(Quaternion::from_axis_angle(&Vector::unit_z(), 30.0).unwrap());
+    assert!(transform.is_dirty());
+
+    let tangent = transform.model_matrix() * Vector::vector(1.0, -1.0, 0.0);
+    let normal = transform.normal_matrix() * Vector::vector(1.0, 1.0, 0.0);
+    assert!(tangent.dot(&normal).fuzzy_eq(&0.0));
+}
@@ -1,29 +1,35 @@
-use rand::Rng;
-use rand::thread_rng;
 use std::collections::HashMap;
 use wgpu::BindGroupLayout;
 use wgpu::Device;
+use wgpu::IndexFormat;
 use wgpu::Queue;
 use wgpu::RenderPass;
 use winit::dpi::PhysicalSize;
 
 use crate::content::{
     WithGPUBuffer,
+    handle::{HandleMap, ObjectHandle},
     model_object::ModelObject,
-    scene::{Scene, generate_scene},
+    scene::{Camera, Scene, generate_scene},
+    scene_graph::SceneGraph,
 };
-use crate::math::algebra::matrix::Matrix;
+use crate::math::algebra::{matrix::Matrix, quaternion::Quaternion, vector::Vector};
+use crate::physics::light::Light;
 
 pub struct World {
     scene: Scene,
-    objects: HashMap<u32, ModelObject>,
+    objects: HandleMap<ModelObject>,
+    names: HashMap<String, ObjectHandle>,
+    graph: SceneGraph,
 }
 
 impl World {
     pub fn new(screen_size: PhysicalSize<u32>) -> Self {
         Self {
             scene: generate_scene(screen_size),
-            objects: HashMap::new(),
+            objects: HandleMap::new(),
+            names: HashMap::new(),
+            graph: SceneGraph::new(),
         }
     }
 
@@ -31,48 +37,234 @@ impl World {
         self.scene.resize(size);
     }
 
-    pub fn add_object(&mut self, model: ModelObject) {
-        let mut rng = thread_rng();
-        let id: u32 = rng.r#gen();
-        self.objects.insert(id, model);
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.scene.camera
     }
 
-    pub fn move_obj(&mut self, translation: Matrix<4>) {
-        self.objects.values_mut().for_each(|model| {
-            model.move_obj(translation);
+    /// index of the light in the scene
+    pub fn add_light(&mut self, light: Light) -> Result<usize, String> {
+        self.scene.add_light(light)
+    }
+
+    pub fn lights_mut(&mut self) -> &mut [Light] {
+        self.scene.lights_mut()
+    }
+
+    pub fn add_object(&mut self, model: ModelObject) -> ObjectHandle {
+        let handle = self.objects.insert(model);
+        self.graph.insert(handle, None).unwrap();
+        handle
+    }
+
+    /// name must be unique in the world
+    pub fn add_named_object(
+        &mut self,
+        name: &str,
+        model: ModelObject,
+    ) -> Result<ObjectHandle, String> {
+        if self.names.contains_key(name) {
+            return Err(format!("name {name} already used"));
+        }
+        let handle = self.add_object(model);
+        self.names.insert(name.to_string(), handle);
+        Ok(handle)
+    }
+
+    /// the model transform is relative to the parent
+    pub fn add_child(
+        &mut self,
+        parent: ObjectHandle,
+        model: ModelObject,
+    ) -> Result<ObjectHandle, String> {
+        if !self.objects.contains(parent) {
+            return Err("parent not found".to_string());
+        }
+        let handle = self.add_object(model);
+        self.set_parent(handle, Some(parent))?;
+        Ok(handle)
+    }
+
+    /// children of the removed object become root objects
+    pub fn remove_object(&mut self, handle: ObjectHandle) -> Option<ModelObject> {
+        let model = self.objects.remove(handle)?;
+        self.graph
+            .children(handle)
+            .to_vec()
+            .into_iter()
+            .for_each(|child| {
+                if let Some(child) = self.objects.get_mut(child) {
+                    child.transform.mark_dirty();
+                }
+            });
+        self.graph.remove(handle);
+        self.names.retain(|_, item| *item != handle);
+        Some(model)
+    }
+
+    pub fn get(&self, handle: ObjectHandle) -> Option<&ModelObject> {
+        self.objects.get(handle)
+    }
+
+    pub fn get_mut(&mut self, handle: ObjectHandle) -> Option<&mut ModelObject> {
+        self.objects.get_mut(handle)
+    }
+
+    pub fn find(&self, name: &str) -> Option<ObjectHandle> {
+        self.names.get(name).copied()
+    }
+
+    pub fn get_by_name(&self, name: &str) -> Option<&ModelObject> {
+        self.find(name).and_then(|handle| self.get(handle))
+    }
+
+    /// in the order objects were added
+    pub fn iter(&self) -> impl Iterator<Item = (ObjectHandle, &ModelObject)> {
+        self.objects.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// keep the local transform, which is now relative to the new parent
+    pub fn set_parent(
+        &mut self,
+        handle: ObjectHandle,
+        parent: Option<ObjectHandle>,
+    ) -> Result<(), String> {
+        self.graph.set_parent(handle, parent)?;
+        if let Some(model) = self.objects.get_mut(handle) {
+            model.transform.mark_dirty();
+        }
+        Ok(())
+    }
+
+    pub fn get_parent(&self, handle: ObjectHandle) -> Option<ObjectHandle> {
+        self.graph.parent(handle)
+    }
+
+    pub fn get_children(&self, handle: ObjectHandle) -> &[ObjectHandle] {
+        self.graph.children(handle)
+    }
+
+    /// world matrix updated by `update_world_transform`
+    pub fn world_matrix(&self, handle: ObjectHandle) -> Option<Matrix<4>> {
+        self.objects.get(handle).map(|model| model.world_matrix())
+    }
+
+    /// apply parent world matrix to children, only changed branches are recalculated
+    pub fn update_world_transform(&mut self) {
+        let mut changed: HashMap<ObjectHandle, bool> = HashMap::new();
+        self.graph.traverse().into_iter().for_each(|handle| {
+            let parent = self.graph.parent(handle);
+            let parent_matrix = parent.and_then(|parent| self.world_matrix(parent));
+            let parent_changed = parent
+                .and_then(|parent| changed.get(&parent).copied())
+                .unwrap_or(false);
+            if let Some(model) = self.objects.get_mut(handle) {
+                changed.insert(
+                    handle,
+                    model.update_world(parent_matrix.as_ref(), parent_changed),
+                );
+            }
+        });
+    }
+
+    /// move the root objects, children follow their parent
+    pub fn move_obj(&mut self, translation: Vector) {
+        self.graph.roots().iter().for_each(|handle| {
+            if let Some(model) = self.objects.get_mut(*handle) {
+                model.move_obj(translation);
+            }
         });
     }
 
-    pub fn rotate_obj(&mut self, rotation: Matrix<4>) {
-        self.objects.values_mut().for_each(|geo| {
-            geo.rotate_obj(rotation);
+    /// rotate the root objects, children follow their parent
+    pub fn rotate_obj(&mut self, rotation: Quaternion) {
+        self.graph.roots().iter().for_each(|handle| {
+            if let Some(model) = self.objects.get_mut(*handle) {
+                model.rotate_obj(rotation);
+            }
         });
     }
 
     pub fn set_pipeline(&self, render_pass: &mut RenderPass) {
         render_pass.set_bind_group(0, self.scene.scene_bind_group.as_ref().unwrap(), &[]);
-        self.objects.values().for_each(|object| {
+        self.objects.iter().for_each(|(_, object)| {
             render_pass.set_bind_group(1, object.transform_bind_group.as_ref().unwrap(), &[]);
+            render_pass.set_bind_group(2, object.material_bind_group.as_ref().unwrap(), &[]);
             render_pass.set_vertex_buffer(0, object.vertex_buffer.as_ref().unwrap().slice(..));
-            render_pass.draw(0..object.vertex_data.len() as u32, 0..1);
+            render_pass.set_index_buffer(
+                object.index_buffer.as_ref().unwrap().slice(..),
+                IndexFormat::Uint32,
+            );
+            render_pass.draw_indexed(0..object.mesh.indices.len() as u32, 0, 0..1);
         });
     }
 }
 
 impl WithGPUBuffer for World {
     fn init_buffer(&mut self, device: &Device, bind_group_layout: &[BindGroupLayout]) {
+        self.update_world_transform();
         self.scene.init_buffer(device, &bind_group_layout[0..=0]);
-        if bind_group_layout.len() == 2 {
+        if bind_group_layout.len() == 3 {
             self.objects.values_mut().for_each(|obj| {
-                obj.init_buffer(device, &bind_group_layout[1..=1]);
+                obj.init_buffer(device, &bind_group_layout[1..=2]);
             });
         }
     }
 
     fn update_buffer(&mut self, queue: &Queue) {
+        self.update_world_transform();
         self.scene.update_buffer(queue);
         self.objects.values_mut().for_each(|obj| {
             obj.update_buffer(queue);
         });
     }
 }
+
+#[test]
+fn test_hierarchy() {
+    use crate::content::{mesh::Mesh, transform::Transform};
+    use crate::math::algebra::{common::FuzzyEq, point::Point};
+
+    fn object(x: f32, y: f32, z: f32) -> ModelObject {
+        let mut transform = Transform::default();
+        transform.set_translation(Vector::vector(x, y, z));
+        ModelObject::new(Mesh::new(vec![], vec![]), transform)
+    }
+
+    let mut world = World::new(PhysicalSize::new(800, 600));
+    let teapot = world
+        .add_named_object("teapot", object(10.0, 0.0, 0.0))
+        .unwrap();
+    let lid = world.add_child(teapot, object(0.0, 5.0, 0.0)).unwrap();
+    world.update_world_transform();
+    let origin = world.world_matrix(lid).unwrap() * Point::origin();
+    assert!(origin.fuzzy_eq(&Point::point(10.0, 5.0, 0.0)));
+
+    // children follow the parent
+    world.move_obj(Vector::vector(0.0, 0.0, 1.0));
+    world.update_world_transform();
+    let origin = world.world_matrix(lid).unwrap() * Point::origin();
+    assert!(origin.fuzzy_eq(&Point::point(10.0, 5.0, 1.0)));
+
+    assert!(world.set_parent(teapot, Some(lid)).is_err());
+    world.set_parent(lid, None).unwrap();
+    world.update_world_transform();
+    let origin = world.world_matrix(lid).unwrap() * Point::origin();
+    assert!(origin.fuzzy_eq(&Point::point(0.0, 5.0, 0.0)));
+
+    assert_eq!(world.find("teapot"), Some(teapot));
+    assert!(world.remove_object(teapot).is_some());
+    assert!(world.get(teapot).is_none());
+    assert!(world.find("teapot").is_none());
+    assert_eq!(
+        world.iter().map(|(handle, _)| handle).collect::<Vec<_>>(),
+        vec![lid]
+    );
+}
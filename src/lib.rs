@@ -0,0 +1,8 @@
+pub mod app;
+pub mod common;
+pub mod constant;
+pub mod content;
+pub mod math;
+pub mod physics;
+pub mod render;
+pub mod tracer;
@@ -1,15 +1,6 @@
+use r_gpu::app::App;
 use winit::{error::EventLoopError, event_loop::EventLoop};
 
-use crate::app::App;
-
-mod app;
-mod common;
-mod constant;
-mod content;
-mod math;
-mod physics;
-mod render;
-
 fn main() -> Result<(), EventLoopError> {
     let event_loop = EventLoop::new().unwrap();
     let mut app = App::default();
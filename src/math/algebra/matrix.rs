@@ -44,7 +44,7 @@ impl<const D: usize> Matrix<D> {
     }
 
     pub fn get_raw(&self) -> [[f32; D]; D] {
-        return self.data;
+        self.data
     }
 }
 
@@ -93,7 +93,7 @@ impl<const D: usize> Mul for Matrix<D> {
         for r in 0..D {
             for c in 0..D {
                 for i in 0..D {
-                    res[r][c] = res[r][c] + self[r][i] * rhs[i][c];
+                    res[r][c] += self[r][i] * rhs[i][c];
                 }
             }
         }
@@ -122,7 +122,7 @@ impl Determinant for Matrix<4> {
     fn det(&self) -> f32 {
         let mut det: f32 = 0.0;
         for col in 0..4 {
-            det = det + self.cofactor(0, col) * self[0][col]
+            det += self.cofactor(0, col) * self[0][col]
         }
         det
     }
@@ -161,7 +161,7 @@ impl Matrix<4> {
     }
 
     fn cofactor(&self, row: usize, col: usize) -> f32 {
-        if (row + col) % 2 == 0 {
+        if (row + col).is_multiple_of(2) {
             self.sub(row, col).det()
         } else {
             -self.sub(row, col).det()
@@ -188,6 +188,18 @@ impl Matrix<4> {
         }
     }
 
+    /// inverse transpose without translation, keeps normals perpendicular
+    /// to the surface under non-uniform scale, identity if not invertible
+    pub fn normal_matrix(&self) -> Self {
+        let mut normal = self
+            .inverse()
+            .map(|inverse| inverse.transpose())
+            .unwrap_or(Matrix::identity());
+        // normals are vectors, translation does not apply
+        normal[3] = [0.0, 0.0, 0.0, 1.0];
+        normal
+    }
+
     pub fn translation(x: f32, y: f32, z: f32) -> Self {
         Matrix::<4> {
             data: [
@@ -246,29 +258,43 @@ impl Matrix<4> {
         }
     }
 
-    // view:(with,height,near,far)
-    pub fn perspective(view: Point, eye: Point, _eye_direction: Vector) -> Self {
-        let view = view.get_raw();
-        // move eye to the origin
-        let translation = Self::translation(-eye.get_x(), -eye.get_y(), -eye.get_z());
-        //TODO: look to negative z direction
-        let rotation = Matrix::identity();
-
-        let perspective_projection = Matrix {
+    /// view matrix, move eye to the origin and look to negative z direction
+    pub fn look_at(eye: Point, target: Point, up: Vector) -> Result<Self, String> {
+        let forward = Vector::from_points(&eye, &target).unit()?;
+        let right = forward.cross(&up).unit()?;
+        let up = right.cross(&forward);
+        let eye = eye.to_vector();
+        Ok(Matrix::<4> {
             data: [
-                [2.0 * (view[2]) / view[0], 0.0, 0.0, 0.0], // x
-                [0.0, 2.0 * (view[2]) / view[1], 0.0, 0.0], // y
                 [
-                    0.0,
-                    0.0,
-                    1.0 / (view[2] - view[3]),
-                    view[2] / (view[2] - view[3]),
-                ], // z
-                [0.0, 0.0, -1.0, 0.0],
+                    right.get_x(),
+                    right.get_y(),
+                    right.get_z(),
+                    -right.dot(&eye),
+                ],
+                [up.get_x(), up.get_y(), up.get_z(), -up.dot(&eye)],
+                [
+                    -forward.get_x(),
+                    -forward.get_y(),
+                    -forward.get_z(),
+                    forward.dot(&eye),
+                ],
+                [0.0, 0.0, 0.0, 1.0],
             ],
-        };
+        })
+    }
 
-        return perspective_projection * rotation * translation;
+    /// fov_y in degree, depth of view space [-near, -far] maps to [0, 1]
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        let f = 1.0 / (deg_to_rad(fov_y) / 2.0).tan();
+        Matrix::<4> {
+            data: [
+                [f / aspect, 0.0, 0.0, 0.0],
+                [0.0, f, 0.0, 0.0],
+                [0.0, 0.0, far / (near - far), near * far / (near - far)],
+                [0.0, 0.0, -1.0, 0.0],
+            ],
+        }
     }
 }
 
@@ -300,6 +326,19 @@ impl<T: Dimension4<Value = f32>> Mul<T> for Matrix<4> {
 fn test_matrix() {
     use crate::math::algebra::vector::Vector;
 
-    let vector = Matrix::<4>::rotate_z(std::f32::consts::PI / 2.0) * Vector::vector(1.0, 0.0, 0.0);
+    let vector = Matrix::<4>::rotate_z(90.0) * Vector::vector(1.0, 0.0, 0.0);
     assert!(Vector::vector(0.0, 1.0, 0.0).fuzzy_eq(&vector))
 }
+
+#[test]
+fn test_look_at() {
+    let view = Matrix::<4>::look_at(
+        Point::point(0.0, 0.0, 10.0),
+        Point::point(10.0, 0.0, 10.0),
+        Vector::unit_y(),
+    )
+    .unwrap();
+    // target in front of the eye
+    let target = view * Point::point(10.0, 0.0, 10.0);
+    assert!(Point::point(0.0, 0.0, -10.0).fuzzy_eq(&target));
+}
@@ -1,5 +1,6 @@
 pub mod common;
 pub mod matrix;
 pub mod point;
+pub mod polynomial;
 pub mod quaternion;
 pub mod vector;
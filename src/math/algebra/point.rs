@@ -32,6 +32,7 @@ impl Point {
         *self == Self::origin()
     }
 
+    #[allow(clippy::self_named_constructors)]
     pub fn point(x: f32, y: f32, z: f32) -> Self {
         Self { x, y, z, w: 1.0 }
     }
@@ -0,0 +1,137 @@
+use std::f64::consts::PI;
+
+// coefficients below are treated as zero
+const ZERO: f64 = 1e-12;
+// newton iterations to polish closed form roots
+const POLISH_STEPS: usize = 2;
+
+/// real roots of a x^2 + b x + c = 0, ascending
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() < ZERO {
+        return if b.abs() < ZERO { vec![] } else { vec![-c / b] };
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return vec![];
+    }
+    // avoid cancellation of b and the square root
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let mut roots = if q.abs() < ZERO {
+        vec![-b / (2.0 * a)]
+    } else {
+        vec![q / a, c / q]
+    };
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+/// real roots of a x^3 + b x^2 + c x + d = 0, ascending
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if a.abs() < ZERO {
+        return solve_quadratic(b, c, d);
+    }
+    let (b, c, d) = (b / a, c / a, d / a);
+    // t^3 + p t + q = 0 with x = t - b / 3
+    let shift = -b / 3.0;
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b.powi(3) / 27.0 - b * c / 3.0 + d;
+    let discriminant = (q / 2.0).powi(2) + (p / 3.0).powi(3);
+    let mut roots = if p.abs() < ZERO {
+        vec![(-q).cbrt()]
+    } else if discriminant > ZERO {
+        let sqrt = discriminant.sqrt();
+        vec![(-q / 2.0 + sqrt).cbrt() + (-q / 2.0 - sqrt).cbrt()]
+    } else if discriminant > -ZERO {
+        let u = (-q / 2.0).cbrt();
+        vec![2.0 * u, -u]
+    } else {
+        // three real roots
+        let r = 2.0 * (-p / 3.0).sqrt();
+        let phi = ((3.0 * q / (2.0 * p)) * (-3.0 / p).sqrt())
+            .clamp(-1.0, 1.0)
+            .acos()
+            / 3.0;
+        (0..3)
+            .map(|k| r * (phi - 2.0 * PI * k as f64 / 3.0).cos())
+            .collect()
+    };
+    roots.iter_mut().for_each(|root| *root += shift);
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+/// real roots of a x^4 + b x^3 + c x^2 + d x + e = 0 by Ferrari's method, ascending
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if a.abs() < ZERO {
+        return solve_cubic(b, c, d, e);
+    }
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+    // y^4 + p y^2 + q y + r = 0 with x = y - b / 4
+    let shift = -b / 4.0;
+    let p = c - 3.0 * b * b / 8.0;
+    let q = d - b * c / 2.0 + b.powi(3) / 8.0;
+    let r = e - b * d / 4.0 + b * b * c / 16.0 - 3.0 * b.powi(4) / 256.0;
+
+    let mut roots: Vec<f64> = if q.abs() < ZERO {
+        // biquadratic
+        solve_quadratic(1.0, p, r)
+            .into_iter()
+            .filter(|z| *z >= 0.0)
+            .flat_map(|z| [z.sqrt(), -z.sqrt()])
+            .collect()
+    } else {
+        // largest root of the resolvent cubic is positive
+        let Some(m) = solve_cubic(1.0, p, p * p / 4.0 - r, -q * q / 8.0)
+            .into_iter()
+            .next_back()
+            .filter(|m| *m > 0.0)
+        else {
+            return vec![];
+        };
+        let s = (2.0 * m).sqrt();
+        let mut roots = solve_quadratic(1.0, s, p / 2.0 + m - q / (2.0 * s));
+        roots.extend(solve_quadratic(1.0, -s, p / 2.0 + m + q / (2.0 * s)));
+        roots
+    };
+    roots.iter_mut().for_each(|root| {
+        *root += shift;
+        *root = polish(*root, [1.0, b, c, d, e]);
+    });
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+// newton steps on the monic quartic
+fn polish(mut x: f64, coefficients: [f64; 5]) -> f64 {
+    (0..POLISH_STEPS).for_each(|_| {
+        let (value, derivative) = coefficients
+            .iter()
+            .fold((0.0, 0.0), |(value, derivative), coefficient| {
+                (value * x + coefficient, derivative * x + value)
+            });
+        if derivative.abs() > ZERO {
+            x -= value / derivative;
+        }
+    });
+    x
+}
+
+#[test]
+fn test_solve() {
+    let close = |a: &[f64], b: &[f64]| {
+        a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-9)
+    };
+    assert!(close(&solve_quadratic(1.0, -3.0, 2.0), &[1.0, 2.0]));
+    assert!(close(&solve_cubic(1.0, -6.0, 11.0, -6.0), &[1.0, 2.0, 3.0]));
+    // (x - 1)(x - 2)(x - 3)(x - 4)
+    assert!(close(
+        &solve_quartic(1.0, -10.0, 35.0, -50.0, 24.0),
+        &[1.0, 2.0, 3.0, 4.0]
+    ));
+    // (x^2 + 1)(x - 1)(x + 2)
+    assert!(close(
+        &solve_quartic(1.0, 1.0, -1.0, 1.0, -2.0),
+        &[-2.0, 1.0]
+    ));
+    assert!(solve_quartic(1.0, 0.0, 0.0, 0.0, 1.0).is_empty());
+}
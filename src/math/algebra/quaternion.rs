@@ -1,6 +1,14 @@
 use std::ops::{Add, Div, Mul, Neg, Sub};
 
-use super::common::FuzzyEq;
+use super::{
+    common::{Dimension4, FuzzyEq, deg_to_rad},
+    matrix::Matrix,
+    point::Point,
+    vector::Vector,
+};
+
+// above this cosine the two rotations are too close for slerp to be stable
+const SLERP_THRESHOLD: f32 = 0.9995;
 
 #[derive(Clone, Copy, Debug)]
 pub struct Quaternion {
@@ -78,6 +86,143 @@ impl Quaternion {
     pub fn unit(&self) -> Result<Self, String> {
         *self / self.norm()
     }
+
+    pub fn identity() -> Self {
+        Self::new(1.0, 0.0, 0.0, 0.0)
+    }
+
+    /// rotation of deg around axis, counterclockwise like `Matrix::rotate_x`
+    pub fn from_axis_angle(axis: &Vector, deg: f32) -> Result<Self, String> {
+        let axis = axis.unit()?;
+        let half = deg_to_rad(deg) / 2.0;
+        let (sin, cos) = half.sin_cos();
+        Ok(Self::new(
+            cos,
+            axis.get_x() * sin,
+            axis.get_y() * sin,
+            axis.get_z() * sin,
+        ))
+    }
+
+    /// rotate around x first, then y, then z, in degree,
+    /// same as `rotate_z(z) * rotate_y(y) * rotate_x(x)`
+    pub fn from_euler(x: f32, y: f32, z: f32) -> Self {
+        let q_x = Self::from_axis_angle(&Vector::unit_x(), x).unwrap();
+        let q_y = Self::from_axis_angle(&Vector::unit_y(), y).unwrap();
+        let q_z = Self::from_axis_angle(&Vector::unit_z(), z).unwrap();
+        q_z * q_y * q_x
+    }
+
+    /// rotation part of the matrix, the matrix should be orthonormal
+    pub fn from_matrix(m: &Matrix<4>) -> Self {
+        let trace = m[0][0] + m[1][1] + m[2][2];
+        let q = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Self::new(
+                s / 4.0,
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+            Self::new(
+                (m[2][1] - m[1][2]) / s,
+                s / 4.0,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+            Self::new(
+                (m[0][2] - m[2][0]) / s,
+                (m[0][1] + m[1][0]) / s,
+                s / 4.0,
+                (m[1][2] + m[2][1]) / s,
+            )
+        } else {
+            let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+            Self::new(
+                (m[1][0] - m[0][1]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                s / 4.0,
+            )
+        };
+        q.unit().unwrap_or(Self::identity())
+    }
+
+    /// rotation matrix of a unit quaternion
+    pub fn to_matrix(&self) -> Matrix<4> {
+        let (r, i, j, k) = self.get_value();
+        Matrix::<4> {
+            data: [
+                [
+                    1.0 - 2.0 * (j * j + k * k),
+                    2.0 * (i * j - k * r),
+                    2.0 * (i * k + j * r),
+                    0.0,
+                ],
+                [
+                    2.0 * (i * j + k * r),
+                    1.0 - 2.0 * (i * i + k * k),
+                    2.0 * (j * k - i * r),
+                    0.0,
+                ],
+                [
+                    2.0 * (i * k - j * r),
+                    2.0 * (j * k + i * r),
+                    1.0 - 2.0 * (i * i + j * j),
+                    0.0,
+                ],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    pub fn rotate_vector(&self, v: &Vector) -> Vector {
+        let p = Self::new(0.0, v.get_x(), v.get_y(), v.get_z());
+        let (_, x, y, z) = (*self * p * self.conjugate()).get_value();
+        Vector::vector(x, y, z)
+    }
+
+    /// rotate around the origin
+    pub fn rotate_point(&self, p: &Point) -> Point {
+        let (x, y, z) = self.rotate_vector(&p.to_vector()).get_value();
+        Point::point(x, y, z)
+    }
+
+    pub fn dot(&self, other: &Self) -> f32 {
+        let (r_1, i_1, j_1, k_1) = self.get_value();
+        let (r_2, i_2, j_2, k_2) = other.get_value();
+        r_1 * r_2 + i_1 * i_2 + j_1 * j_2 + k_1 * k_2
+    }
+
+    /// normalized linear interpolation, along the shorter arc
+    pub fn nlerp(&self, other: &Self, t: f32) -> Self {
+        let end = if self.dot(other) < 0.0 {
+            -*other
+        } else {
+            *other
+        };
+        (*self * (1.0 - t) + end * t).unit().unwrap_or(*self)
+    }
+
+    /// spherical linear interpolation, along the shorter arc
+    pub fn slerp(&self, other: &Self, t: f32) -> Self {
+        let mut cos = self.dot(other);
+        let mut end = *other;
+        if cos < 0.0 {
+            cos = -cos;
+            end = -end;
+        }
+        if cos > SLERP_THRESHOLD {
+            return self.nlerp(&end, t);
+        }
+        let theta = cos.acos();
+        let sin = theta.sin();
+        *self * (((1.0 - t) * theta).sin() / sin) + end * ((t * theta).sin() / sin)
+    }
 }
 
 impl FuzzyEq for Quaternion {
@@ -121,6 +266,14 @@ impl Sub for Quaternion {
     }
 }
 
+impl Mul for Quaternion {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.hamilton_multiplied_by(rhs)
+    }
+}
+
 impl Mul<f32> for Quaternion {
     type Output = Self;
 
@@ -144,3 +297,21 @@ impl Div<f32> for Quaternion {
         }
     }
 }
+
+#[test]
+fn test_rotation() {
+    let q = Quaternion::from_axis_angle(&Vector::unit_z(), 90.0).unwrap();
+    assert!(q.to_matrix().fuzzy_eq(&Matrix::<4>::rotate_z(90.0)));
+    assert!(
+        q.rotate_vector(&Vector::unit_x())
+            .fuzzy_eq(&Vector::unit_y())
+    );
+
+    let euler = Quaternion::from_euler(-90.0, 90.0, 0.0);
+    let m = Matrix::<4>::rotate_y(90.0) * Matrix::<4>::rotate_x(-90.0);
+    assert!(euler.to_matrix().fuzzy_eq(&m));
+    assert!(Quaternion::from_matrix(&m).to_matrix().fuzzy_eq(&m));
+
+    let half = Quaternion::identity().slerp(&q, 0.5);
+    assert!(half.fuzzy_eq(&Quaternion::from_axis_angle(&Vector::unit_z(), 45.0).unwrap()));
+}
@@ -18,6 +18,7 @@ unsafe impl bytemuck::Zeroable for Vector {}
 unsafe impl bytemuck::Pod for Vector {}
 
 impl Vector {
+    #[allow(clippy::self_named_constructors)]
     pub fn vector(x: f32, y: f32, z: f32) -> Self {
         Self { x, y, z, w: 0.0 }
     }
@@ -101,7 +102,7 @@ impl Vector {
     }
 
     pub fn norm(&self) -> f32 {
-        self.dot(&self).sqrt()
+        self.dot(self).sqrt()
     }
 
     pub fn unit(&self) -> Result<Self, String> {
@@ -139,6 +140,19 @@ impl Vector {
             Ok(-*self + (*normal) * 2.0 * self.dot(normal))
         }
     }
+
+    /// refract by Snell's law, self and normal are unit vectors pointing away from
+    /// the surface, eta is n_incoming / n_transmitted,
+    /// None for total internal reflection
+    pub fn refract(&self, normal: &Self, eta: f32) -> Option<Self> {
+        let cos_i = self.dot(normal);
+        let sin2_t = eta.powi(2) * (1.0 - cos_i.powi(2));
+        if sin2_t > 1.0 {
+            return None;
+        }
+        let cos_t = (1.0 - sin2_t).sqrt();
+        Some(*normal * (eta * cos_i - cos_t) - *self * eta)
+    }
 }
 
 impl Dimension4 for Vector {
@@ -227,6 +241,26 @@ impl Div<f32> for Vector {
 
 #[cfg(test)]
 mod test {
+    use crate::math::algebra::{common::FuzzyEq, vector::Vector};
+
     #[test]
     fn vector_work() {}
+
+    #[test]
+    fn refract() {
+        let normal = Vector::unit_y();
+        // straight through at normal incidence
+        let refracted = normal.refract(&normal, 1.0 / 1.5).unwrap();
+        assert!(refracted.fuzzy_eq(&-normal));
+
+        // 45 degree into glass bends towards the normal
+        let eye_v = Vector::vector(-1.0, 1.0, 0.0).unit().unwrap();
+        let refracted = eye_v.refract(&normal, 1.0 / 1.5).unwrap();
+        let sin_t = 0.5f32.sqrt() / 1.5;
+        assert!(refracted.fuzzy_eq(&Vector::vector(sin_t, -(1.0 - sin_t * sin_t).sqrt(), 0.0)));
+        assert!(refracted.norm().fuzzy_eq(&1.0));
+
+        // total internal reflection out of glass
+        assert_eq!(eye_v.refract(&normal, 1.5), None);
+    }
 }
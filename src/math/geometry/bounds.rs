@@ -0,0 +1,151 @@
+use crate::math::algebra::{common::Dimension4, matrix::Matrix, point::Point};
+
+use super::ray::Ray;
+
+/// axis aligned bounding box, may be empty or infinite
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    min: Point,
+    max: Point,
+}
+
+impl Aabb {
+    pub fn new(a: Point, b: Point) -> Self {
+        Self::empty().include(&a).include(&b)
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            min: Point::point(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: Point::point(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        }
+    }
+
+    pub fn infinite() -> Self {
+        Self {
+            min: Point::point(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+            max: Point::point(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+        }
+    }
+
+    pub fn get_min(&self) -> Point {
+        self.min
+    }
+
+    pub fn get_max(&self) -> Point {
+        self.max
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..3).any(|axis| axis_of(&self.min, axis) > axis_of(&self.max, axis))
+    }
+
+    pub fn is_finite(&self) -> bool {
+        !self.is_empty()
+            && (0..3).all(|axis| {
+                axis_of(&self.min, axis).is_finite() && axis_of(&self.max, axis).is_finite()
+            })
+    }
+
+    pub fn include(&self, point: &Point) -> Self {
+        let (x, y, z) = point.get_value();
+        Self {
+            min: Point::point(
+                self.min.get_x().min(x),
+                self.min.get_y().min(y),
+                self.min.get_z().min(z),
+            ),
+            max: Point::point(
+                self.max.get_x().max(x),
+                self.max.get_y().max(y),
+                self.max.get_z().max(z),
+            ),
+        }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.include(&other.min).include(&other.max)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self {
+            min: Point::point(
+                self.min.get_x().max(other.min.get_x()),
+                self.min.get_y().max(other.min.get_y()),
+                self.min.get_z().max(other.min.get_z()),
+            ),
+            max: Point::point(
+                self.max.get_x().min(other.max.get_x()),
+                self.max.get_y().min(other.max.get_y()),
+                self.max.get_z().min(other.max.get_z()),
+            ),
+        }
+    }
+
+    pub fn centroid(&self) -> Point {
+        Point::point(
+            (self.min.get_x() + self.max.get_x()) / 2.0,
+            (self.min.get_y() + self.max.get_y()) / 2.0,
+            (self.min.get_z() + self.max.get_z()) / 2.0,
+        )
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let (x, y, z) = (
+            self.max.get_x() - self.min.get_x(),
+            self.max.get_y() - self.min.get_y(),
+            self.max.get_z() - self.min.get_z(),
+        );
+        2.0 * (x * y + y * z + z * x)
+    }
+
+    /// box around the transformed corners
+    pub fn transform(&self, m: &Matrix<4>) -> Self {
+        if !self.is_finite() {
+            return if self.is_empty() {
+                *self
+            } else {
+                Self::infinite()
+            };
+        }
+        (0..8).fold(Self::empty(), |bounds, corner| {
+            let pick = |axis: usize| {
+                if corner & (1 << axis) == 0 {
+                    axis_of(&self.min, axis)
+                } else {
+                    axis_of(&self.max, axis)
+                }
+            };
+            bounds.include(&(*m * Point::point(pick(0), pick(1), pick(2))))
+        })
+    }
+
+    /// entering and leaving t of the ray within [t_min, t_max]
+    pub fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<(f32, f32)> {
+        let (mut enter, mut leave) = (t_min, t_max);
+        for axis in 0..3 {
+            let inverse = 1.0 / axis_of_direction(ray, axis);
+            let origin = axis_of(&ray.origin, axis);
+            let t_1 = (axis_of(&self.min, axis) - origin) * inverse;
+            let t_2 = (axis_of(&self.max, axis) - origin) * inverse;
+            // NaN from a parallel ray on the slab border is ignored by min and max
+            enter = enter.max(t_1.min(t_2));
+            leave = leave.min(t_1.max(t_2));
+            if enter > leave {
+                return None;
+            }
+        }
+        Some((enter, leave))
+    }
+}
+
+pub(super) fn axis_of(point: &Point, axis: usize) -> f32 {
+    point.get_raw()[axis]
+}
+
+fn axis_of_direction(ray: &Ray, axis: usize) -> f32 {
+    ray.direction.get_raw()[axis]
+}
@@ -0,0 +1,369 @@
+use super::{
+    bounds::{Aabb, axis_of},
+    common::Intersection,
+    ray::Ray,
+};
+
+// items in a leaf without trying to split
+const LEAF_SIZE: usize = 2;
+// a leaf never holds more items, even if splitting costs more
+const MAX_LEAF_SIZE: usize = 16;
+const SAH_BINS: usize = 12;
+// cost of a box test relative to an item intersection
+const TRAVERSAL_COST: f32 = 0.125;
+
+#[derive(Debug)]
+enum BvhNode {
+    Leaf {
+        bounds: Aabb,
+        start: usize,
+        count: usize,
+    },
+    // the left child follows the node
+    Inner {
+        bounds: Aabb,
+        right: usize,
+    },
+}
+
+impl BvhNode {
+    fn bounds(&self) -> &Aabb {
+        match self {
+            BvhNode::Leaf { bounds, .. } | BvhNode::Inner { bounds, .. } => bounds,
+        }
+    }
+}
+
+/// bounding volume hierarchy over item indices, built with the surface area
+/// heuristic, items with infinite bounds are tested on every query
+#[derive(Debug)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    // item indices ordered by leaf
+    indices: Vec<usize>,
+    unbounded: Vec<usize>,
+}
+
+impl Bvh {
+    /// bounds of every item, by item index
+    pub fn new(bounds: &[Aabb]) -> Self {
+        let (mut indices, mut unbounded) = (vec![], vec![]);
+        bounds.iter().enumerate().for_each(|(index, bound)| {
+            if bound.is_finite() {
+                indices.push(index);
+            } else if !bound.is_empty() {
+                unbounded.push(index);
+            }
+        });
+        let mut bvh = Self {
+            nodes: vec![],
+            indices,
+            unbounded,
+        };
+        if !bvh.indices.is_empty() {
+            bvh.build(0, bvh.indices.len(), bounds);
+        }
+        bvh
+    }
+
+    pub fn get_bounds(&self) -> Aabb {
+        if !self.unbounded.is_empty() {
+            return Aabb::infinite();
+        }
+        self.nodes
+            .first()
+            .map(|node| *node.bounds())
+            .unwrap_or(Aabb::empty())
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// nearest intersection with t in [t_min, t_max), with the item index,
+    /// intersect gives the intersections of the item at the index
+    pub fn nearest(
+        &self,
+        ray: &Ray,
+        t_min: f32,
+        t_max: f32,
+        intersect: impl Fn(usize) -> Vec<Intersection>,
+    ) -> Option<(usize, Intersection)> {
+        let mut closest = t_max;
+        let mut nearest = None;
+        let mut test = |index: usize, closest: &mut f32| {
+            intersect(index).into_iter().for_each(|inter| {
+                if inter.get_t() >= t_min && inter.get_t() < *closest {
+                    *closest = inter.get_t();
+                    nearest = Some((index, inter));
+                }
+            });
+        };
+        self.unbounded
+            .iter()
+            .for_each(|index| test(*index, &mut closest));
+        let mut stack = if self.nodes.is_empty() {
+            vec![]
+        } else {
+            vec![0]
+        };
+        while let Some(node) = stack.pop() {
+            if self.nodes[node].bounds().hit(ray, t_min, closest).is_none() {
+                continue;
+            }
+            match &self.nodes[node] {
+                BvhNode::Leaf { start, count, .. } => self.indices[*start..*start + *count]
+                    .iter()
+                    .for_each(|index| test(*index, &mut closest)),
+                BvhNode::Inner { right, .. } => {
+                    // visit the nearer child first
+                    let enter = |child: usize| {
+                        self.nodes[child]
+                            .bounds()
+                            .hit(ray, t_min, closest)
+                            .map(|(enter, _)| enter)
+                            .unwrap_or(f32::INFINITY)
+                    };
+                    let (left, right) = (node + 1, *right);
+                    if enter(left) <= enter(right) {
+                        stack.push(right);
+                        stack.push(left);
+                    } else {
+                        stack.push(left);
+                        stack.push(right);
+                    }
+                }
+            }
+        }
+        nearest
+    }
+
+    /// true if any intersection has t in [t_min, t_max), stops at the first one
+    pub fn any(
+        &self,
+        ray: &Ray,
+        t_min: f32,
+        t_max: f32,
+        intersect: impl Fn(usize) -> Vec<Intersection>,
+    ) -> bool {
+        let test = |index: &usize| {
+            intersect(*index)
+                .iter()
+                .any(|inter| inter.get_t() >= t_min && inter.get_t() < t_max)
+        };
+        if self.unbounded.iter().any(test) {
+            return true;
+        }
+        let mut stack = if self.nodes.is_empty() {
+            vec![]
+        } else {
+            vec![0]
+        };
+        while let Some(node) = stack.pop() {
+            if self.nodes[node].bounds().hit(ray, t_min, t_max).is_none() {
+                continue;
+            }
+            match &self.nodes[node] {
+                BvhNode::Leaf { start, count, .. } => {
+                    if self.indices[*start..*start + *count].iter().any(test) {
+                        return true;
+                    }
+                }
+                BvhNode::Inner { right, .. } => {
+                    stack.push(*right);
+                    stack.push(node + 1);
+                }
+            }
+        }
+        false
+    }
+
+    /// every intersection along the whole ray, sorted by t
+    pub fn all(
+        &self,
+        ray: &Ray,
+        intersect: impl Fn(usize) -> Vec<Intersection>,
+    ) -> Vec<(usize, Intersection)> {
+        let mut intersections = vec![];
+        let mut collect = |index: usize| {
+            intersections.extend(intersect(index).into_iter().map(|inter| (index, inter)));
+        };
+        self.unbounded.iter().for_each(|index| collect(*index));
+        let mut stack = if self.nodes.is_empty() {
+            vec![]
+        } else {
+            vec![0]
+        };
+        while let Some(node) = stack.pop() {
+            if self.nodes[node]
+                .bounds()
+                .hit(ray, f32::NEG_INFINITY, f32::INFINITY)
+                .is_none()
+            {
+                continue;
+            }
+            match &self.nodes[node] {
+                BvhNode::Leaf { start, count, .. } => self.indices[*start..*start + *count]
+                    .iter()
+                    .for_each(|index| collect(*index)),
+                BvhNode::Inner { right, .. } => {
+                    stack.push(*right);
+                    stack.push(node + 1);
+                }
+            }
+        }
+        intersections.sort_by(|a, b| a.1.get_t().total_cmp(&b.1.get_t()));
+        intersections
+    }
+
+    // node for indices[start..end], returns the node index
+    fn build(&mut self, start: usize, end: usize, bounds: &[Aabb]) -> usize {
+        let node = self.nodes.len();
+        let items = &self.indices[start..end];
+        let node_bounds = items
+            .iter()
+            .fold(Aabb::empty(), |total, index| total.union(&bounds[*index]));
+        let count = end - start;
+        self.nodes.push(BvhNode::Leaf {
+            bounds: node_bounds,
+            start,
+            count,
+        });
+        if count <= LEAF_SIZE {
+            return node;
+        }
+
+        let centroid_bounds = items.iter().fold(Aabb::empty(), |total, index| {
+            total.include(&bounds[*index].centroid())
+        });
+        // split along the longest axis of the centroids
+        let extent = |axis: usize| {
+            axis_of(&centroid_bounds.get_max(), axis) - axis_of(&centroid_bounds.get_min(), axis)
+        };
+        let axis = (0..3)
+            .max_by(|a, b| extent(*a).total_cmp(&extent(*b)))
+            .unwrap();
+        let (low, width) = (axis_of(&centroid_bounds.get_min(), axis), extent(axis));
+        let bin_of = |index: usize| {
+            if width <= 0.0 {
+                return 0;
+            }
+            let offset = (axis_of(&bounds[index].centroid(), axis) - low) / width;
+            ((offset * SAH_BINS as f32) as usize).min(SAH_BINS - 1)
+        };
+
+        let split = if width > 0.0 {
+            let mut bins = [(Aabb::empty(), 0_usize); SAH_BINS];
+            items.iter().for_each(|index| {
+                let bin = &mut bins[bin_of(*index)];
+                bin.0 = bin.0.union(&bounds[*index]);
+                bin.1 += 1;
+            });
+            // cost of splitting after each bin
+            let area = node_bounds.surface_area().max(f32::MIN_POSITIVE);
+            (1..SAH_BINS)
+                .map(|split| {
+                    let side = |bins: &[(Aabb, usize)]| {
+                        bins.iter().fold((Aabb::empty(), 0), |(total, count), bin| {
+                            (total.union(&bin.0), count + bin.1)
+                        })
+                    };
+                    let (left, left_count) = side(&bins[..split]);
+                    let (right, right_count) = side(&bins[split..]);
+                    let cost = TRAVERSAL_COST
+                        + (left.surface_area() * left_count as f32
+                            + right.surface_area() * right_count as f32)
+                            / area;
+                    (split, cost, left_count)
+                })
+                .filter(|(_, _, left_count)| *left_count > 0 && *left_count < count)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .filter(|(_, cost, _)| *cost < count as f32 || count > MAX_LEAF_SIZE)
+                .map(|(split, _, _)| split)
+        } else {
+            None
+        };
+
+        let middle = match split {
+            Some(split) => {
+                self.indices[start..end].sort_by_key(|index| bin_of(*index) >= split);
+                start
+                    + self.indices[start..end]
+                        .iter()
+                        .filter(|index| bin_of(**index) < split)
+                        .count()
+            }
+            // same centroids, split by count if the leaf is too large
+            None if count > MAX_LEAF_SIZE => start + count / 2,
+            None => return node,
+        };
+        self.build(start, middle, bounds);
+        let right = self.build(middle, end, bounds);
+        self.nodes[node] = BvhNode::Inner {
+            bounds: node_bounds,
+            right,
+        };
+        node
+    }
+}
+
+#[test]
+fn nearest_as_linear() {
+    use super::{common::Intersect, sphere::Sphere};
+    use crate::math::algebra::{point::Point, vector::Vector};
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let spheres: Vec<Sphere> = (0..500)
+        .map(|_| {
+            Sphere::new(
+                Point::point(
+                    rng.gen_range(-50.0..50.0),
+                    rng.gen_range(-50.0..50.0),
+                    rng.gen_range(-50.0..50.0),
+                ),
+                rng.gen_range(0.5..3.0),
+            )
+        })
+        .collect();
+    let bounds: Vec<Aabb> = spheres.iter().map(|sphere| sphere.bounds()).collect();
+    let bvh = Bvh::new(&bounds);
+    assert!(bvh.node_count() > 1);
+
+    (0..200).for_each(|_| {
+        let ray = Ray::new(
+            Point::point(0.0, 0.0, -100.0),
+            Vector::vector(rng.gen_range(-0.5..0.5), rng.gen_range(-0.5..0.5), 1.0),
+        )
+        .unwrap();
+        let linear = spheres
+            .iter()
+            .enumerate()
+            .flat_map(|(index, sphere)| sphere.intersect(&ray).into_iter().map(move |i| (index, i)))
+            .filter(|(_, inter)| inter.get_t() >= 0.0)
+            .min_by(|a, b| a.1.get_t().total_cmp(&b.1.get_t()));
+        let nearest = bvh.nearest(&ray, 0.0, f32::INFINITY, |index| {
+            spheres[index].intersect(&ray)
+        });
+        assert_eq!(
+            linear.map(|(index, _)| index),
+            nearest.as_ref().map(|(index, _)| *index)
+        );
+        let any = bvh.any(&ray, 0.0, f32::INFINITY, |index| {
+            spheres[index].intersect(&ray)
+        });
+        assert_eq!(any, nearest.is_some());
+        let all = bvh.all(&ray, |index| spheres[index].intersect(&ray));
+        assert_eq!(
+            all.iter().filter(|(_, inter)| inter.get_t() >= 0.0).count(),
+            spheres
+                .iter()
+                .map(|sphere| sphere
+                    .intersect(&ray)
+                    .iter()
+                    .filter(|inter| inter.get_t() >= 0.0)
+                    .count())
+                .sum::<usize>()
+        );
+    });
+}
@@ -1,19 +1,33 @@
-use crate::math::algebra::{point::Point, vector::Vector};
+use crate::{
+    constant::OVER_POINT_OFFSET,
+    math::algebra::{point::Point, vector::Vector},
+};
 
-use super::ray::Ray;
+use super::{bounds::Aabb, ray::Ray};
 
 pub type IntersectTargetID = u128;
 
-pub trait Intersect {
+/// shapes are shared by the render threads
+pub trait Intersect: Send + Sync {
     fn intersect(&self, ray: &Ray) -> Vec<Intersection>;
     fn norm_at(&self, point: &Point) -> Result<Vector, String>;
+    /// box around the shape, infinite for unbounded shapes
+    fn bounds(&self) -> Aabb;
 }
 
+#[derive(Debug, Clone, Copy)]
 pub struct Intersection {
     t: f32,
     ray_direction: Vector,
     surface_point: Point,
+    // outward normal of the surface
     normal_v: Vector,
+    // surface point in the space of the shape
+    object_point: Point,
+    // object the intersection comes from, set by the owner of the shapes
+    target: Option<IntersectTargetID>,
+    // barycentric coordinates of triangles
+    uv: Option<(f32, f32)>,
 }
 
 impl Intersection {
@@ -23,9 +37,40 @@ impl Intersection {
             ray_direction,
             surface_point,
             normal_v,
+            object_point: surface_point,
+            target: None,
+            uv: None,
         }
     }
 
+    pub fn with_object_point(mut self, object_point: Point) -> Self {
+        self.object_point = object_point;
+        self
+    }
+
+    pub fn with_target(mut self, target: IntersectTargetID) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    pub fn with_uv(mut self, u: f32, v: f32) -> Self {
+        self.uv = Some((u, v));
+        self
+    }
+
+    /// replace the surface, e.g. mapped to another space, keeps the other records
+    pub fn with_surface(
+        mut self,
+        ray_direction: Vector,
+        surface_point: Point,
+        normal_v: Vector,
+    ) -> Self {
+        self.ray_direction = ray_direction;
+        self.surface_point = surface_point;
+        self.normal_v = normal_v;
+        self
+    }
+
     pub fn get_t(&self) -> f32 {
         self.t
     }
@@ -42,7 +87,129 @@ impl Intersection {
         self.surface_point
     }
 
+    pub fn get_object_point(&self) -> Point {
+        self.object_point
+    }
+
+    pub fn get_target(&self) -> Option<IntersectTargetID> {
+        self.target
+    }
+
+    pub fn get_uv(&self) -> Option<(f32, f32)> {
+        self.uv
+    }
+
     pub fn get_eye_v(&self) -> Vector {
         -self.ray_direction.unit().unwrap_or(Vector::unit_z())
     }
+
+    /// true if the ray hits the outside of the surface
+    pub fn is_front_face(&self) -> bool {
+        self.normal_v.dot(&self.ray_direction) <= 0.0
+    }
+
+    /// true if the ray comes from inside of the shape
+    pub fn is_inside(&self) -> bool {
+        !self.is_front_face()
+    }
+
+    /// normal on the side of the eye
+    pub fn get_shading_normal(&self) -> Vector {
+        if self.is_front_face() {
+            self.normal_v
+        } else {
+            -self.normal_v
+        }
+    }
+
+    /// slightly above the surface on the side of the eye, origin of shadow and reflection rays
+    pub fn get_over_point(&self) -> Point {
+        self.surface_point + self.get_shading_normal() * OVER_POINT_OFFSET
+    }
+
+    /// slightly below the surface, origin of refraction rays
+    pub fn get_under_point(&self) -> Point {
+        self.surface_point + self.get_shading_normal() * -OVER_POINT_OFFSET
+    }
+}
+
+/// intersections along a ray sorted by t
+#[derive(Debug, Clone, Default)]
+pub struct Intersections {
+    intersections: Vec<Intersection>,
+}
+
+impl Intersections {
+    pub fn new(mut intersections: Vec<Intersection>) -> Self {
+        intersections.sort_by(|a, b| a.get_t().total_cmp(&b.get_t()));
+        Self { intersections }
+    }
+
+    pub fn len(&self) -> usize {
+        self.intersections.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intersections.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Intersection> {
+        self.intersections.get(index)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Intersection> {
+        self.intersections.iter()
+    }
+
+    /// index of the hit
+    pub fn hit_index(&self) -> Option<usize> {
+        self.intersections
+            .iter()
+            .position(|inter| inter.get_t() >= 0.0)
+    }
+
+    /// nearest intersection not behind the ray origin
+    pub fn hit(&self) -> Option<&Intersection> {
+        self.hit_index().map(|index| &self.intersections[index])
+    }
+}
+
+impl From<Vec<Intersection>> for Intersections {
+    fn from(intersections: Vec<Intersection>) -> Self {
+        Self::new(intersections)
+    }
+}
+
+impl IntoIterator for Intersections {
+    type Item = Intersection;
+    type IntoIter = std::vec::IntoIter<Intersection>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.intersections.into_iter()
+    }
+}
+
+#[test]
+fn test_hit() {
+    let ray = Ray::new(Point::point(0.0, 0.0, -5.0), Vector::unit_z()).unwrap();
+    let at = |t: f32| Intersection::new(t, ray.direction, ray.point_at(t), -Vector::unit_z());
+    let intersections = Intersections::new(vec![at(5.0), at(-1.0), at(2.0), at(7.0)]);
+    assert_eq!(intersections.hit().map(|hit| hit.get_t()), Some(2.0));
+    assert_eq!(intersections.hit_index(), Some(1));
+    assert!(Intersections::new(vec![at(-2.0), at(-1.0)]).hit().is_none());
+
+    let hit = at(5.0).with_target(3);
+    assert_eq!(hit.get_target(), Some(3));
+    assert!(hit.is_front_face());
+    assert!(hit.get_over_point().get_value().2 < 0.0);
+
+    // from inside the normal is flipped to the eye
+    let inside = Intersection::new(
+        1.0,
+        ray.direction,
+        Point::point(0.0, 0.0, 1.0),
+        Vector::unit_z(),
+    );
+    assert!(inside.is_inside());
+    assert_eq!(inside.get_shading_normal(), -Vector::unit_z());
 }
@@ -0,0 +1,127 @@
+use crate::{
+    constant::{EPSILON, SURFACE_EPSILON},
+    math::algebra::{common::Dimension4, point::Point, vector::Vector},
+};
+
+use super::{
+    bounds::Aabb,
+    common::{Intersect, Intersection},
+    cylinder::{cap_hits, intersections},
+    ray::Ray,
+};
+
+/// double cone x^2 + z^2 = y^2 around the y axis, between minimum and maximum y,
+/// closed with caps or open
+#[derive(Debug, Clone, Copy)]
+pub struct Cone {
+    minimum: f32,
+    maximum: f32,
+    closed: bool,
+}
+
+impl Cone {
+    pub fn new(minimum: f32, maximum: f32, closed: bool) -> Self {
+        Self {
+            minimum: minimum.min(maximum),
+            maximum: minimum.max(maximum),
+            closed,
+        }
+    }
+
+    /// open on both ends, endless along y
+    pub fn infinite() -> Self {
+        Self::new(f32::NEG_INFINITY, f32::INFINITY, false)
+    }
+}
+
+impl Intersect for Cone {
+    fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        let (o_x, o_y, o_z) = ray.origin.get_value();
+        let (d_x, d_y, d_z) = ray.direction.get_value();
+        let a = d_x.powi(2) - d_y.powi(2) + d_z.powi(2);
+        let b = 2.0 * (o_x * d_x - o_y * d_y + o_z * d_z);
+        let c = o_x.powi(2) - o_y.powi(2) + o_z.powi(2);
+
+        let wall = if a.abs() < EPSILON {
+            // parallel to one half of the cone
+            if b.abs() < EPSILON {
+                vec![]
+            } else {
+                vec![-c / (2.0 * b)]
+            }
+        } else {
+            let discriminant = b.powi(2) - 4.0 * a * c;
+            if discriminant < 0.0 {
+                vec![]
+            } else {
+                vec![
+                    (-b - discriminant.sqrt()) / (2.0 * a),
+                    (-b + discriminant.sqrt()) / (2.0 * a),
+                ]
+            }
+        };
+        let mut t_list: Vec<f32> = wall
+            .into_iter()
+            .filter(|t| {
+                let y = ray.point_at(*t).get_y();
+                self.minimum < y && y < self.maximum
+            })
+            .collect();
+        if self.closed {
+            t_list.extend(cap_hits(ray, self.minimum, self.minimum.abs()));
+            t_list.extend(cap_hits(ray, self.maximum, self.maximum.abs()));
+        }
+        intersections(self, ray, t_list)
+    }
+
+    fn norm_at(&self, point: &Point) -> Result<Vector, String> {
+        let (x, y, z) = point.get_value();
+        let distance = x.powi(2) + z.powi(2);
+        if distance < self.maximum.powi(2) && y >= self.maximum - SURFACE_EPSILON {
+            Ok(Vector::unit_y())
+        } else if distance < self.minimum.powi(2) && y <= self.minimum + SURFACE_EPSILON {
+            Ok(-Vector::unit_y())
+        } else {
+            let radius = distance.sqrt();
+            // apex
+            if radius < EPSILON {
+                return Ok(Vector::unit_y() * -y.signum());
+            }
+            let n_y = if y > 0.0 { -radius } else { radius };
+            Vector::vector(x, n_y, z).unit()
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        let radius = self.minimum.abs().max(self.maximum.abs());
+        Aabb::new(
+            Point::point(-radius, self.minimum, -radius),
+            Point::point(radius, self.maximum, radius),
+        )
+    }
+}
+
+#[test]
+fn ray_cone() {
+    use crate::math::algebra::common::FuzzyEq;
+
+    let cone = Cone::infinite();
+    let ray = Ray::new(Point::point(0.0, 1.0, -5.0), Vector::unit_z()).unwrap();
+    let hits = cone.intersect(&ray);
+    assert!(hits[0].get_t().fuzzy_eq(&4.0) && hits[1].get_t().fuzzy_eq(&6.0));
+
+    // parallel to one half
+    let ray = Ray::new(Point::point(0.0, 0.0, -1.0), Vector::vector(0.0, 1.0, 1.0)).unwrap();
+    let hits = cone.intersect(&ray);
+    assert_eq!(hits.len(), 1);
+    assert!((hits[0].get_t() - 0.35355).abs() < 1e-4);
+
+    // caps
+    let cone = Cone::new(-0.5, 0.5, true);
+    let ray = Ray::new(Point::point(0.0, 0.0, -0.25), Vector::unit_y()).unwrap();
+    assert_eq!(cone.intersect(&ray).len(), 4);
+
+    let normal = cone.norm_at(&Point::point(1.0, 1.0, 1.0)).unwrap();
+    let expect = Vector::vector(1.0, -(2.0_f32).sqrt(), 1.0).unit().unwrap();
+    assert_eq!(normal, expect);
+}
@@ -0,0 +1,169 @@
+use crate::math::algebra::{point::Point, vector::Vector};
+
+use super::{
+    bounds::Aabb,
+    common::{Intersect, Intersection},
+    ray::Ray,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    // left minus right
+    Difference,
+}
+
+impl CsgOperation {
+    /// keep an intersection by which side it comes from and whether the ray
+    /// is inside the left and right shape
+    fn allowed(&self, left_hit: bool, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => (left_hit && !in_right) || (!left_hit && !in_left),
+            CsgOperation::Intersection => (left_hit && in_right) || (!left_hit && in_left),
+            CsgOperation::Difference => (left_hit && !in_right) || (!left_hit && in_left),
+        }
+    }
+}
+
+/// combination of two closed shapes, both must return every intersection
+/// along the ray, including those behind the origin
+pub struct Csg {
+    operation: CsgOperation,
+    left: Box<dyn Intersect>,
+    right: Box<dyn Intersect>,
+}
+
+impl Csg {
+    pub fn new(
+        operation: CsgOperation,
+        left: Box<dyn Intersect>,
+        right: Box<dyn Intersect>,
+    ) -> Self {
+        Self {
+            operation,
+            left,
+            right,
+        }
+    }
+
+    pub fn union(left: Box<dyn Intersect>, right: Box<dyn Intersect>) -> Self {
+        Self::new(CsgOperation::Union, left, right)
+    }
+
+    pub fn intersection(left: Box<dyn Intersect>, right: Box<dyn Intersect>) -> Self {
+        Self::new(CsgOperation::Intersection, left, right)
+    }
+
+    pub fn difference(left: Box<dyn Intersect>, right: Box<dyn Intersect>) -> Self {
+        Self::new(CsgOperation::Difference, left, right)
+    }
+
+    pub fn get_operation(&self) -> CsgOperation {
+        self.operation
+    }
+
+    /// keep the intersections on the surface of the combined shape, sorted by t,
+    /// each tagged with true if it comes from the left shape
+    pub fn filter(&self, intersections: Vec<(bool, Intersection)>) -> Vec<Intersection> {
+        let mut intersections = intersections;
+        intersections.sort_by(|a, b| a.1.get_t().total_cmp(&b.1.get_t()));
+        let (mut in_left, mut in_right) = (false, false);
+        intersections
+            .into_iter()
+            .filter_map(|(left_hit, inter)| {
+                let allowed = self.operation.allowed(left_hit, in_left, in_right);
+                if left_hit {
+                    in_left = !in_left;
+                } else {
+                    in_right = !in_right;
+                }
+                allowed.then_some((left_hit, inter))
+            })
+            .map(|(left_hit, inter)| {
+                // surface of the removed shape faces into it
+                if !left_hit && self.operation == CsgOperation::Difference {
+                    flip_normal(inter)
+                } else {
+                    inter
+                }
+            })
+            .collect()
+    }
+}
+
+fn flip_normal(inter: Intersection) -> Intersection {
+    inter.with_surface(
+        inter.get_ray_direction(),
+        inter.get_surface_point(),
+        -inter.get_normal(),
+    )
+}
+
+impl Intersect for Csg {
+    fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        let left = self
+            .left
+            .intersect(ray)
+            .into_iter()
+            .map(|inter| (true, inter));
+        let right = self
+            .right
+            .intersect(ray)
+            .into_iter()
+            .map(|inter| (false, inter));
+        self.filter(left.chain(right).collect())
+    }
+
+    /// normal of the left shape, or of the right one if the point is not on the left
+    fn norm_at(&self, point: &Point) -> Result<Vector, String> {
+        self.left.norm_at(point).or_else(|_| {
+            self.right.norm_at(point).map(|normal| {
+                if self.operation == CsgOperation::Difference {
+                    -normal
+                } else {
+                    normal
+                }
+            })
+        })
+    }
+
+    fn bounds(&self) -> Aabb {
+        match self.operation {
+            CsgOperation::Union => self.left.bounds().union(&self.right.bounds()),
+            CsgOperation::Intersection => self.left.bounds().intersection(&self.right.bounds()),
+            CsgOperation::Difference => self.left.bounds(),
+        }
+    }
+}
+
+#[test]
+fn csg_filter() {
+    use super::sphere::Sphere;
+    use crate::math::algebra::common::FuzzyEq;
+
+    let spheres = || -> (Box<dyn Intersect>, Box<dyn Intersect>) {
+        (
+            Box::new(Sphere::new(Point::origin(), 1.0)),
+            Box::new(Sphere::new(Point::point(0.0, 0.0, 0.5), 1.0)),
+        )
+    };
+    let ray = Ray::new(Point::point(0.0, 0.0, -5.0), Vector::unit_z()).unwrap();
+    [
+        (CsgOperation::Union, [4.0, 6.5]),
+        (CsgOperation::Intersection, [4.5, 6.0]),
+        (CsgOperation::Difference, [4.0, 4.5]),
+    ]
+    .into_iter()
+    .for_each(|(operation, expect)| {
+        let (left, right) = spheres();
+        let hits = Csg::new(operation, left, right).intersect(&ray);
+        assert_eq!(hits.len(), 2);
+        assert!(hits[0].get_t().fuzzy_eq(&expect[0]) && hits[1].get_t().fuzzy_eq(&expect[1]));
+    });
+
+    // the hollow of the difference faces out of the solid
+    let (left, right) = spheres();
+    let hits = Csg::difference(left, right).intersect(&ray);
+    assert_eq!(hits[1].get_normal(), Vector::unit_z());
+}
@@ -0,0 +1,97 @@
+use crate::{
+    constant::EPSILON,
+    math::algebra::{point::Point, vector::Vector},
+};
+
+use super::{
+    bounds::Aabb,
+    common::{Intersect, Intersection},
+    ray::Ray,
+};
+
+/// axis aligned cube from -1 to 1 on each axis, place it with `Transformed`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Cube;
+
+impl Cube {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+// entering and leaving t of the slab from -1 to 1
+fn slab(origin: f32, direction: f32) -> (f32, f32) {
+    if direction.abs() < EPSILON {
+        // parallel to the slab
+        if origin.abs() <= 1.0 {
+            (f32::NEG_INFINITY, f32::INFINITY)
+        } else {
+            (f32::INFINITY, f32::NEG_INFINITY)
+        }
+    } else {
+        let t_1 = (-1.0 - origin) / direction;
+        let t_2 = (1.0 - origin) / direction;
+        (t_1.min(t_2), t_1.max(t_2))
+    }
+}
+
+impl Intersect for Cube {
+    fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        let (o_x, o_y, o_z) = ray.origin.get_value();
+        let (d_x, d_y, d_z) = ray.direction.get_value();
+        let (t_min, t_max) = [slab(o_x, d_x), slab(o_y, d_y), slab(o_z, d_z)]
+            .into_iter()
+            .fold(
+                (f32::NEG_INFINITY, f32::INFINITY),
+                |(low, high), (t_1, t_2)| (low.max(t_1), high.min(t_2)),
+            );
+        if t_min > t_max || !t_min.is_finite() || !t_max.is_finite() {
+            return vec![];
+        }
+        [t_min, t_max]
+            .into_iter()
+            .filter_map(|t| {
+                let point = ray.point_at(t);
+                self.norm_at(&point)
+                    .ok()
+                    .map(|normal| Intersection::new(t, ray.direction, point, normal))
+            })
+            .collect()
+    }
+
+    /// normal of the face with the largest coordinate
+    fn norm_at(&self, point: &Point) -> Result<Vector, String> {
+        let (x, y, z) = point.get_value();
+        let (a_x, a_y, a_z) = (x.abs(), y.abs(), z.abs());
+        if a_x >= a_y && a_x >= a_z {
+            Ok(Vector::vector(x.signum(), 0.0, 0.0))
+        } else if a_y >= a_z {
+            Ok(Vector::vector(0.0, y.signum(), 0.0))
+        } else {
+            Ok(Vector::vector(0.0, 0.0, z.signum()))
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::new(Point::point(-1.0, -1.0, -1.0), Point::point(1.0, 1.0, 1.0))
+    }
+}
+
+#[test]
+fn ray_cube() {
+    use crate::math::algebra::common::FuzzyEq;
+
+    let cube = Cube::new();
+    let ray = Ray::new(Point::point(5.0, 0.5, 0.0), Vector::vector(-1.0, 0.0, 0.0)).unwrap();
+    let hits = cube.intersect(&ray);
+    assert!(hits[0].get_t().fuzzy_eq(&4.0) && hits[1].get_t().fuzzy_eq(&6.0));
+    assert_eq!(hits[0].get_normal(), Vector::unit_x());
+
+    // inside
+    let ray = Ray::new(Point::origin(), Vector::unit_z()).unwrap();
+    let hits = cube.intersect(&ray);
+    assert!(hits[0].get_t().fuzzy_eq(&-1.0) && hits[1].get_t().fuzzy_eq(&1.0));
+
+    let ray = Ray::new(Point::point(2.0, 0.0, 2.0), Vector::vector(0.0, 0.0, -1.0)).unwrap();
+    assert!(cube.intersect(&ray).is_empty());
+}
@@ -0,0 +1,142 @@
+use crate::{
+    constant::{EPSILON, SURFACE_EPSILON},
+    math::algebra::{common::Dimension4, point::Point, vector::Vector},
+};
+
+use super::{
+    bounds::Aabb,
+    common::{Intersect, Intersection},
+    ray::Ray,
+};
+
+/// cylinder of radius 1 around the y axis, between minimum and maximum y,
+/// closed with caps or open
+#[derive(Debug, Clone, Copy)]
+pub struct Cylinder {
+    minimum: f32,
+    maximum: f32,
+    closed: bool,
+}
+
+impl Cylinder {
+    pub fn new(minimum: f32, maximum: f32, closed: bool) -> Self {
+        Self {
+            minimum: minimum.min(maximum),
+            maximum: minimum.max(maximum),
+            closed,
+        }
+    }
+
+    /// open on both ends, endless along y
+    pub fn infinite() -> Self {
+        Self::new(f32::NEG_INFINITY, f32::INFINITY, false)
+    }
+}
+
+impl Intersect for Cylinder {
+    fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        let (o_x, _, o_z) = ray.origin.get_value();
+        let (d_x, _, d_z) = ray.direction.get_value();
+        let mut t_list = vec![];
+
+        let a = d_x.powi(2) + d_z.powi(2);
+        // parallel to the y axis never hits the wall
+        if a.abs() > EPSILON {
+            let b = 2.0 * (o_x * d_x + o_z * d_z);
+            let c = o_x.powi(2) + o_z.powi(2) - 1.0;
+            let discriminant = b.powi(2) - 4.0 * a * c;
+            if discriminant < 0.0 {
+                return vec![];
+            }
+            [
+                (-b - discriminant.sqrt()) / (2.0 * a),
+                (-b + discriminant.sqrt()) / (2.0 * a),
+            ]
+            .into_iter()
+            .filter(|t| {
+                let y = ray.point_at(*t).get_y();
+                self.minimum < y && y < self.maximum
+            })
+            .for_each(|t| t_list.push(t));
+        }
+        if self.closed {
+            t_list.extend(cap_hits(ray, self.minimum, 1.0));
+            t_list.extend(cap_hits(ray, self.maximum, 1.0));
+        }
+        intersections(self, ray, t_list)
+    }
+
+    fn norm_at(&self, point: &Point) -> Result<Vector, String> {
+        let (x, y, z) = point.get_value();
+        let distance = x.powi(2) + z.powi(2);
+        if distance < 1.0 && y >= self.maximum - SURFACE_EPSILON {
+            Ok(Vector::unit_y())
+        } else if distance < 1.0 && y <= self.minimum + SURFACE_EPSILON {
+            Ok(-Vector::unit_y())
+        } else {
+            Vector::vector(x, 0.0, z).unit()
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::new(
+            Point::point(-1.0, self.minimum, -1.0),
+            Point::point(1.0, self.maximum, 1.0),
+        )
+    }
+}
+
+/// t where the ray hits the cap at height y within the radius
+pub(super) fn cap_hits(ray: &Ray, y: f32, radius: f32) -> Option<f32> {
+    let d_y = ray.direction.get_y();
+    if d_y.abs() < EPSILON || !y.is_finite() {
+        return None;
+    }
+    let t = (y - ray.origin.get_y()) / d_y;
+    let (x, _, z) = ray.point_at(t).get_value();
+    (x.powi(2) + z.powi(2) <= radius.powi(2)).then_some(t)
+}
+
+/// sorted intersections at the given t, with normals of the shape
+pub(super) fn intersections(
+    shape: &impl Intersect,
+    ray: &Ray,
+    mut t_list: Vec<f32>,
+) -> Vec<Intersection> {
+    t_list.sort_by(f32::total_cmp);
+    t_list
+        .into_iter()
+        .filter_map(|t| {
+            let point = ray.point_at(t);
+            shape
+                .norm_at(&point)
+                .ok()
+                .map(|normal| Intersection::new(t, ray.direction, point, normal))
+        })
+        .collect()
+}
+
+#[test]
+fn ray_cylinder() {
+    use crate::math::algebra::common::FuzzyEq;
+
+    let cylinder = Cylinder::new(1.0, 2.0, true);
+    // through both caps
+    let ray = Ray::new(Point::point(0.0, 3.0, 0.0), Vector::vector(0.0, -1.0, 0.0)).unwrap();
+    let hits = cylinder.intersect(&ray);
+    assert!(hits[0].get_t().fuzzy_eq(&1.0) && hits[1].get_t().fuzzy_eq(&2.0));
+    assert_eq!(hits[0].get_normal(), Vector::unit_y());
+    assert_eq!(hits[1].get_normal(), -Vector::unit_y());
+
+    // wall and cap
+    let ray = Ray::new(Point::point(0.0, 1.2, -2.0), Vector::vector(0.0, 1.0, 2.0)).unwrap();
+    let hits = cylinder.intersect(&ray);
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[0].get_normal(), -Vector::unit_z());
+    assert_eq!(hits[1].get_normal(), Vector::unit_y());
+
+    // outside of the open range
+    let ray = Ray::new(Point::point(0.0, 3.0, -5.0), Vector::unit_z()).unwrap();
+    assert!(Cylinder::new(1.0, 2.0, false).intersect(&ray).is_empty());
+    assert_eq!(Cylinder::infinite().intersect(&ray).len(), 2);
+}
@@ -21,9 +21,9 @@ impl<const D: usize> GridPoint<D> {
         // length : 2*D
         for i in 0..D {
             let mut location = self.location;
-            location[i] = location[i] - 1;
+            location[i] -= 1;
             adjacent.push(GridPoint::<D>::new(location));
-            location[i] = location[i] + 2;
+            location[i] += 2;
             adjacent.push(GridPoint::<D>::new(location))
         }
         adjacent
@@ -32,7 +32,7 @@ impl<const D: usize> GridPoint<D> {
     pub fn translate(&self, translation: [i64; D]) -> Self {
         let mut location = self.location;
         for i in 0..D {
-            location[i] = location[i] + translation[i];
+            location[i] += translation[i];
         }
         Self::new(location)
     }
@@ -67,7 +67,7 @@ impl<const D: usize> GridPoint<D> {
         match location.get_mut(i) {
             None => None,
             Some(v) => {
-                *v = *v + delta_i;
+                *v += delta_i;
                 Some(Self { location })
             }
         }
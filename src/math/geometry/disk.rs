@@ -0,0 +1,96 @@
+use crate::{
+    constant::EPSILON,
+    math::algebra::{common::Dimension4, point::Point, vector::Vector},
+};
+
+use super::{
+    bounds::Aabb,
+    common::{Intersect, Intersection},
+    ray::Ray,
+};
+
+/// flat disk in the xz plane around the origin facing +y,
+/// a ring if the inner radius is not zero
+#[derive(Debug, Clone, Copy)]
+pub struct Disk {
+    radius: f32,
+    inner_radius: f32,
+}
+
+impl Disk {
+    pub fn new(radius: f32) -> Self {
+        Self {
+            radius,
+            inner_radius: 0.0,
+        }
+    }
+
+    pub fn with_inner_radius(mut self, inner_radius: f32) -> Self {
+        self.inner_radius = inner_radius.clamp(0.0, self.radius);
+        self
+    }
+}
+
+impl Intersect for Disk {
+    fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        let d_y = ray.direction.get_y();
+        if d_y.abs() < EPSILON {
+            return vec![];
+        }
+        let t = -ray.origin.get_y() / d_y;
+        let point = ray.point_at(t);
+        let distance = point.get_x().powi(2) + point.get_z().powi(2);
+        if distance <= self.radius.powi(2) && distance >= self.inner_radius.powi(2) {
+            vec![Intersection::new(t, ray.direction, point, Vector::unit_y())]
+        } else {
+            vec![]
+        }
+    }
+
+    fn norm_at(&self, _: &Point) -> Result<Vector, String> {
+        Ok(Vector::unit_y())
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::new(
+            Point::point(-self.radius, 0.0, -self.radius),
+            Point::point(self.radius, 0.0, self.radius),
+        )
+    }
+}
+
+#[test]
+fn ray_disk() {
+    use crate::math::algebra::common::FuzzyEq;
+
+    let disk = Disk::new(2.0);
+    // inside the radius from above
+    let ray = Ray::new(Point::point(1.0, 3.0, 1.0), Vector::vector(0.0, -1.0, 0.0)).unwrap();
+    let hits = disk.intersect(&ray);
+    assert_eq!(hits.len(), 1);
+    assert!(hits[0].get_t().fuzzy_eq(&3.0));
+    assert_eq!(hits[0].get_normal(), Vector::unit_y());
+
+    // outside the radius
+    let ray = Ray::new(Point::point(2.0, 3.0, 1.0), Vector::vector(0.0, -1.0, 0.0)).unwrap();
+    assert!(disk.intersect(&ray).is_empty());
+
+    // in the hole of a ring, hit on the ring
+    let ring = disk.with_inner_radius(1.0);
+    let ray = Ray::new(Point::point(0.5, 3.0, 0.0), Vector::vector(0.0, -1.0, 0.0)).unwrap();
+    assert!(ring.intersect(&ray).is_empty());
+    let ray = Ray::new(Point::point(1.5, 3.0, 0.0), Vector::vector(0.0, -1.0, 0.0)).unwrap();
+    assert_eq!(ring.intersect(&ray).len(), 1);
+
+    // parallel to the disk
+    let ray = Ray::new(Point::point(-5.0, 0.0, 0.0), Vector::unit_x()).unwrap();
+    assert!(disk.intersect(&ray).is_empty());
+
+    assert_eq!(
+        disk.norm_at(&Point::point(1.0, 0.0, 0.0)),
+        Ok(Vector::unit_y())
+    );
+    let bounds = disk.bounds();
+    assert_eq!(bounds.get_min(), Point::point(-2.0, 0.0, -2.0));
+    assert_eq!(bounds.get_max(), Point::point(2.0, 0.0, 2.0));
+}
@@ -1,7 +1,18 @@
+pub mod bounds;
+pub mod bvh;
 pub mod common;
+pub mod cone;
+pub mod csg;
+pub mod cube;
+pub mod cylinder;
 pub mod discrete;
+pub mod disk;
 pub mod plane;
 pub mod polyhedron;
 pub mod ray;
+pub mod smooth_triangle;
 pub mod sphere;
+pub mod torus;
+pub mod transformed;
 pub mod triangle;
+pub mod triangle_mesh;
@@ -3,6 +3,7 @@ use std::cmp;
 use crate::math::algebra::{common::FuzzyEq, point::Point, vector::Vector};
 
 use super::{
+    bounds::Aabb,
     common::{Intersect, Intersection},
     ray::Ray,
 };
@@ -27,7 +28,7 @@ impl Plane {
 
     pub fn in_plane(&self, point: &Point) -> bool {
         self.norm
-            .dot(&Vector::from_points(&self.center, &point))
+            .dot(&Vector::from_points(&self.center, point))
             .fuzzy_eq(&0.0)
     }
 }
@@ -43,12 +44,14 @@ impl Intersect for Plane {
             let b = self.norm.dot(&ray.direction);
             let t = -(a + d) / b;
             let surface_point = ray.point_at(t);
-            let normal_v_res = self.norm_at(&surface_point);
-            if let (cmp::Ordering::Less, Ok(normal_v)) = (
-                surface_point.distance(&self.center).total_cmp(&self.size),
-                normal_v_res,
-            ) {
-                intersection.push(Intersection::new(t, ray.direction, surface_point, normal_v));
+            if let cmp::Ordering::Less = surface_point.distance(&self.center).total_cmp(&self.size)
+            {
+                intersection.push(Intersection::new(
+                    t,
+                    ray.direction,
+                    surface_point,
+                    self.norm,
+                ));
             }
             intersection
         }
@@ -61,4 +64,9 @@ impl Intersect for Plane {
             Err("Point not in the plane".to_string())
         }
     }
+
+    fn bounds(&self) -> Aabb {
+        let size = Vector::vector(self.size, self.size, self.size);
+        Aabb::new(self.center + -size, self.center + size)
+    }
 }
@@ -1,12 +1,15 @@
 use crate::math::algebra::{point::Point, vector::Vector};
 
 use super::{
+    bounds::Aabb,
+    bvh::Bvh,
     common::{Intersect, Intersection},
     triangle::Triangle,
 };
 
 pub struct Polyhedron {
     triangles: Vec<Triangle>,
+    bvh: Bvh,
 }
 
 impl Polyhedron {
@@ -19,7 +22,11 @@ impl Polyhedron {
                 vertices[surface.2],
             ))
         });
-        Self { triangles }
+        let bounds: Vec<Aabb> = triangles.iter().map(|triangle| triangle.bounds()).collect();
+        Self {
+            bvh: Bvh::new(&bounds),
+            triangles,
+        }
     }
 
     pub fn norm_to(&self, surface_index: usize) -> Option<Vector> {
@@ -33,29 +40,20 @@ impl Polyhedron {
             .iter()
             .enumerate()
             .find(|(_, triangle)| triangle.is_on(point))
-            .and_then(|item| Some(item.0))
+            .map(|item| item.0)
     }
 }
 
 impl Intersect for Polyhedron {
     fn intersect(&self, ray: &super::ray::Ray) -> Vec<Intersection> {
-        let mut intersection = vec![];
-        self.triangles.iter().for_each(|triangle| {
-            intersection.append(&mut triangle.intersect(ray));
-        });
-        //only take the first intersection with minimum t
-        if intersection.len() > 1 {
-            if let Some(min) = intersection
-                .into_iter()
-                .min_by(|a, b| a.get_t().total_cmp(&b.get_t()))
-            {
-                return vec![min];
-            } else {
-                return vec![];
-            }
-        } else {
-            intersection
-        }
+        //only take the first intersection in front of the ray
+        self.bvh
+            .nearest(ray, 0.0, f32::INFINITY, |index| {
+                self.triangles[index].intersect(ray)
+            })
+            .map(|(_, inter)| inter)
+            .into_iter()
+            .collect()
     }
 
     fn norm_at(&self, point: &Point) -> Result<Vector, String> {
@@ -65,4 +63,8 @@ impl Intersect for Polyhedron {
             Err("point not on surface".to_string())
         }
     }
+
+    fn bounds(&self) -> Aabb {
+        self.bvh.get_bounds()
+    }
 }
@@ -0,0 +1,87 @@
+use crate::math::algebra::{point::Point, vector::Vector};
+
+use super::{
+    bounds::Aabb,
+    common::{Intersect, Intersection},
+    ray::Ray,
+    triangle::Triangle,
+};
+
+/// triangle with a normal on each vertex, the normal in between is interpolated
+pub struct SmoothTriangle {
+    triangle: Triangle,
+    n_0: Vector,
+    n_1: Vector,
+    n_2: Vector,
+}
+
+impl SmoothTriangle {
+    pub fn new(
+        (p_0, n_0): (Point, Vector),
+        (p_1, n_1): (Point, Vector),
+        (p_2, n_2): (Point, Vector),
+    ) -> Self {
+        Self {
+            triangle: Triangle::new(p_0, p_1, p_2),
+            n_0,
+            n_1,
+            n_2,
+        }
+    }
+
+    pub fn get_triangle(&self) -> &Triangle {
+        &self.triangle
+    }
+
+    /// face normal if the vertex normals cancel out
+    pub fn normal_at_uv(&self, u: f32, v: f32) -> Option<Vector> {
+        (self.n_0 * (1.0 - u - v) + self.n_1 * u + self.n_2 * v)
+            .unit()
+            .ok()
+            .or(self.triangle.norm())
+    }
+}
+
+impl Intersect for SmoothTriangle {
+    fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        self.triangle
+            .intersect(ray)
+            .into_iter()
+            .filter_map(|inter| {
+                let (u, v) = inter.get_uv()?;
+                let normal = self.normal_at_uv(u, v)?;
+                Some(inter.with_surface(ray.direction, inter.get_surface_point(), normal))
+            })
+            .collect()
+    }
+
+    fn norm_at(&self, point: &Point) -> Result<Vector, String> {
+        self.triangle
+            .barycentric(point)
+            .and_then(|(u, v)| self.normal_at_uv(u, v))
+            .ok_or("invalid triangle".to_string())
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.triangle.bounds()
+    }
+}
+
+#[test]
+fn interpolate_normal() {
+    let triangle = SmoothTriangle::new(
+        (Point::point(0.0, 1.0, 0.0), Vector::unit_y()),
+        (Point::point(-1.0, 0.0, 0.0), -Vector::unit_x()),
+        (Point::point(1.0, 0.0, 0.0), Vector::unit_x()),
+    );
+    let ray = Ray::new(Point::point(-0.2, 0.3, -5.0), Vector::unit_z()).unwrap();
+    let hit = triangle.intersect(&ray)[0];
+    let (u, v) = hit.get_uv().unwrap();
+    assert!((u - 0.45).abs() < 1e-4 && (v - 0.25).abs() < 1e-4);
+
+    let expect = Vector::vector(-0.2, 0.3, 0.0).unit().unwrap();
+    let normal = hit.get_normal();
+    assert!((normal.dot(&expect) - 1.0).abs() < 1e-4);
+    let normal = triangle.norm_at(&hit.get_surface_point()).unwrap();
+    assert!((normal.dot(&expect) - 1.0).abs() < 1e-4);
+}
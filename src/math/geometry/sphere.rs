@@ -1,6 +1,7 @@
 use crate::math::algebra::{common::FuzzyEq, point::Point, vector::Vector};
 
 use super::{
+    bounds::Aabb,
     common::{Intersect, Intersection},
     ray::Ray,
 };
@@ -47,25 +48,21 @@ impl Intersect for Sphere {
         if des < 0.0 {
             intersections
         } else {
-            let t_1 = (-b - des.sqrt()) / (2.0 * a);
-            let surface_point_1 = ray.point_at(t_1);
-            let normal_v_1 = self.norm_at(&surface_point_1).unwrap();
-            intersections.push(Intersection::new(
-                t_1,
-                ray.direction,
-                surface_point_1,
-                normal_v_1,
-            ));
-
-            let t_2 = (-b + des.sqrt()) / (2.0 * a);
-            let surface_point_2 = ray.point_at(t_2);
-            let normal_v_2 = self.norm_at(&surface_point_2).unwrap();
-            intersections.push(Intersection::new(
-                t_2,
-                ray.direction,
-                surface_point_2,
-                normal_v_2,
-            ));
+            // normal from the center, the computed point may be off the surface by rounding
+            [(-b - des.sqrt()) / (2.0 * a), (-b + des.sqrt()) / (2.0 * a)]
+                .into_iter()
+                .for_each(|t| {
+                    let surface_point = ray.point_at(t);
+                    let normal_v = Vector::from_points(&self.origin, &surface_point)
+                        .unit()
+                        .unwrap_or(Vector::unit_y());
+                    intersections.push(Intersection::new(
+                        t,
+                        ray.direction,
+                        surface_point,
+                        normal_v,
+                    ));
+                });
             intersections
         }
     }
@@ -78,6 +75,11 @@ impl Intersect for Sphere {
             Err("Point not on the sphere".to_string())
         }
     }
+
+    fn bounds(&self) -> Aabb {
+        let radius = Vector::vector(self.radius, self.radius, self.radius);
+        Aabb::new(self.origin + -radius, self.origin + radius)
+    }
 }
 
 #[test]
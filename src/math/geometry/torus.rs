@@ -0,0 +1,114 @@
+use crate::math::algebra::{point::Point, polynomial::solve_quartic, vector::Vector};
+
+use super::{
+    bounds::Aabb,
+    common::{Intersect, Intersection},
+    ray::Ray,
+};
+
+/// torus around the y axis, the tube of minor radius goes around a circle of
+/// major radius in the xz plane
+#[derive(Debug, Clone, Copy)]
+pub struct Torus {
+    major_radius: f32,
+    minor_radius: f32,
+}
+
+impl Torus {
+    pub fn new(major_radius: f32, minor_radius: f32) -> Self {
+        Self {
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl Intersect for Torus {
+    fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        let (major, minor) = (self.major_radius as f64, self.minor_radius as f64);
+        let (o_x, o_y, o_z) = ray.origin.get_value();
+        let (d_x, d_y, d_z) = ray.direction.get_value();
+        let (d_x, d_y, d_z) = (d_x as f64, d_y as f64, d_z as f64);
+        let dd = d_x * d_x + d_y * d_y + d_z * d_z;
+        // start from the point closest to the center, keeps the quartic well conditioned
+        let t_c = -(o_x as f64 * d_x + o_y as f64 * d_y + o_z as f64 * d_z) / dd;
+        let (o_x, o_y, o_z) = (
+            o_x as f64 + t_c * d_x,
+            o_y as f64 + t_c * d_y,
+            o_z as f64 + t_c * d_z,
+        );
+        let oo = o_x * o_x + o_y * o_y + o_z * o_z;
+        // miss the bounding sphere
+        if oo > (major + minor).powi(2) {
+            return vec![];
+        }
+
+        // (|p|^2 + R^2 - r^2)^2 = 4 R^2 (x^2 + z^2)
+        let od = o_x * d_x + o_y * d_y + o_z * d_z;
+        let k = oo + major * major - minor * minor;
+        let four_r2 = 4.0 * major * major;
+        solve_quartic(
+            dd * dd,
+            4.0 * dd * od,
+            4.0 * od * od + 2.0 * dd * k - four_r2 * (d_x * d_x + d_z * d_z),
+            4.0 * od * k - 2.0 * four_r2 * (o_x * d_x + o_z * d_z),
+            k * k - four_r2 * (o_x * o_x + o_z * o_z),
+        )
+        .into_iter()
+        .filter_map(|t| {
+            let t = (t + t_c) as f32;
+            let point = ray.point_at(t);
+            self.norm_at(&point)
+                .ok()
+                .map(|normal| Intersection::new(t, ray.direction, point, normal))
+        })
+        .collect()
+    }
+
+    /// from the nearest point on the center circle
+    fn norm_at(&self, point: &Point) -> Result<Vector, String> {
+        let (x, _, z) = point.get_value();
+        let center = Vector::vector(x, 0.0, z)
+            .unit()
+            .map(|direction| Point::origin() + direction * self.major_radius)
+            .unwrap_or(Point::origin());
+        Vector::from_points(&center, point).unit()
+    }
+
+    fn bounds(&self) -> Aabb {
+        let radius = self.major_radius + self.minor_radius;
+        Aabb::new(
+            Point::point(-radius, -self.minor_radius, -radius),
+            Point::point(radius, self.minor_radius, radius),
+        )
+    }
+}
+
+#[test]
+fn ray_torus() {
+    use crate::math::algebra::common::FuzzyEq;
+
+    let torus = Torus::new(2.0, 0.5);
+    // through both sides of the ring
+    let ray = Ray::new(Point::point(-5.0, 0.0, 0.0), Vector::unit_x()).unwrap();
+    let t: Vec<f32> = torus
+        .intersect(&ray)
+        .iter()
+        .map(|hit| hit.get_t())
+        .collect();
+    assert_eq!(t.len(), 4);
+    [2.5, 3.5, 6.5, 7.5]
+        .iter()
+        .zip(&t)
+        .for_each(|(expect, t)| assert!((expect - t).abs() < 1e-4));
+
+    // down through the tube
+    let ray = Ray::new(Point::point(2.0, 5.0, 0.0), -Vector::unit_y()).unwrap();
+    let hits = torus.intersect(&ray);
+    assert!((hits[0].get_t() - 4.5).abs() < 1e-4);
+    assert!(hits[0].get_normal().fuzzy_eq(&Vector::unit_y()));
+
+    // through the hole
+    let ray = Ray::new(Point::point(0.0, 5.0, 0.0), -Vector::unit_y()).unwrap();
+    assert!(torus.intersect(&ray).is_empty());
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use crate::math::algebra::{matrix::Matrix, point::Point, vector::Vector};
+
+use super::{
+    bounds::Aabb,
+    common::{Intersect, Intersection},
+    ray::Ray,
+};
+
+/// shape placed by a transform from object space to world space
+pub struct Transformed<S: Intersect> {
+    shape: S,
+    transform: Matrix<4>,
+    inverse: Matrix<4>,
+    // inverse transpose of the transform
+    normal: Matrix<4>,
+}
+
+impl<S: Intersect> Transformed<S> {
+    pub fn new(shape: S, transform: Matrix<4>) -> Result<Self, String> {
+        Ok(Self {
+            shape,
+            inverse: transform.inverse()?,
+            normal: transform.normal_matrix(),
+            transform,
+        })
+    }
+
+    pub fn get_shape(&self) -> &S {
+        &self.shape
+    }
+
+    pub fn get_transform(&self) -> Matrix<4> {
+        self.transform
+    }
+
+    pub fn set_transform(&mut self, transform: Matrix<4>) -> Result<(), String> {
+        self.inverse = transform.inverse()?;
+        self.normal = transform.normal_matrix();
+        self.transform = transform;
+        Ok(())
+    }
+
+    fn world_normal(&self, object_normal: Vector) -> Vector {
+        (self.normal * object_normal)
+            .unit()
+            .unwrap_or(object_normal)
+    }
+}
+
+impl<S: Intersect> Intersect for Transformed<S> {
+    fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        // direction is not normalized, so t is the same in both spaces
+        let object_ray = ray.transform(self.inverse);
+        self.shape
+            .intersect(&object_ray)
+            .into_iter()
+            .map(|inter| {
+                inter.with_surface(
+                    ray.direction,
+                    ray.point_at(inter.get_t()),
+                    self.world_normal(inter.get_normal()),
+                )
+            })
+            .collect()
+    }
+
+    fn norm_at(&self, point: &Point) -> Result<Vector, String> {
+        self.shape
+            .norm_at(&(self.inverse * *point))
+            .map(|normal| self.world_normal(normal))
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.shape.bounds().transform(&self.transform)
+    }
+}
+
+/// share one shape between many transformed instances
+impl<S: Intersect + ?Sized> Intersect for Arc<S> {
+    fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        self.as_ref().intersect(ray)
+    }
+
+    fn norm_at(&self, point: &Point) -> Result<Vector, String> {
+        self.as_ref().norm_at(point)
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.as_ref().bounds()
+    }
+}
+
+#[test]
+fn scaled_sphere() {
+    use super::sphere::Sphere;
+    use crate::math::algebra::common::FuzzyEq;
+
+    let sphere = Arc::new(Sphere::new(Point::origin(), 1.0));
+    let ray = Ray::new(Point::point(0.0, 0.0, -5.0), Vector::unit_z()).unwrap();
+
+    let scaled = Transformed::new(sphere.clone(), Matrix::scale(2.0, 2.0, 2.0)).unwrap();
+    let t: Vec<f32> = scaled
+        .intersect(&ray)
+        .iter()
+        .map(|inter| inter.get_t())
+        .collect();
+    assert!(t[0].fuzzy_eq(&3.0) && t[1].fuzzy_eq(&7.0));
+
+    let moved = Transformed::new(sphere, Matrix::translation(5.0, 0.0, 0.0)).unwrap();
+    assert!(moved.intersect(&ray).is_empty());
+
+    // normal of a squashed sphere is (x, 4y, z)
+    let squashed = Transformed::new(
+        Sphere::new(Point::origin(), 1.0),
+        Matrix::scale(1.0, 0.5, 1.0),
+    )
+    .unwrap();
+    let ray = Ray::new(Point::point(0.0, 0.25, -5.0), Vector::unit_z()).unwrap();
+    let hit = &squashed.intersect(&ray)[0];
+    let expect = Vector::vector(0.0, 1.0, -(0.75_f32).sqrt()).unit().unwrap();
+    assert!(hit.get_normal().fuzzy_eq(&expect));
+    assert!(
+        hit.get_object_point()
+            .fuzzy_eq(&Point::point(0.0, 0.5, -(0.75_f32).sqrt()))
+    );
+}
@@ -1,6 +1,10 @@
-use crate::math::algebra::{common::FuzzyEq, point::Point, vector::Vector};
+use crate::{
+    constant::EPSILON,
+    math::algebra::{common::FuzzyEq, point::Point, vector::Vector},
+};
 
 use super::{
+    bounds::Aabb,
     common::{Intersect, Intersection},
     ray::Ray,
 };
@@ -31,24 +35,42 @@ impl Triangle {
             .ok()
     }
 
+    /// (u, v) weights of p_1 and p_2 for a point on the plane of the triangle,
+    /// p_0 has weight 1 - u - v
+    pub fn barycentric(&self, p: &Point) -> Option<(f32, f32)> {
+        let edge_1 = Vector::from_points(&self.p_0, &self.p_1);
+        let edge_2 = Vector::from_points(&self.p_0, &self.p_2);
+        let to_p = Vector::from_points(&self.p_0, p);
+        let (d_11, d_12, d_22) = (
+            edge_1.dot(&edge_1),
+            edge_1.dot(&edge_2),
+            edge_2.dot(&edge_2),
+        );
+        let denominator = d_11 * d_22 - d_12 * d_12;
+        if denominator.abs() < EPSILON {
+            return None;
+        }
+        let (d_p1, d_p2) = (to_p.dot(&edge_1), to_p.dot(&edge_2));
+        Some((
+            (d_22 * d_p1 - d_12 * d_p2) / denominator,
+            (d_11 * d_p2 - d_12 * d_p1) / denominator,
+        ))
+    }
+
     pub fn is_on(&self, p: &Point) -> bool {
         if let Ok(norm) = self.norm_at(&self.p_2) {
             // check if the given point is the same as any of the vertices
             if *p == self.p_0 || *p == self.p_1 || *p == self.p_2 {
                 return true;
             }
-            let r_0 = Vector::from_points(&self.p_0, &p);
+            let r_0 = Vector::from_points(&self.p_0, p);
             // check if the given point in the same plane as the triangle
             if norm.dot(&r_0).fuzzy_eq(&0.0) {
                 // check if then given point in the triangle
                 let t_1 = Triangle::new(*p, self.p_0, self.p_1);
                 let t_2 = Triangle::new(*p, self.p_1, self.p_2);
                 let t_3 = Triangle::new(*p, self.p_2, self.p_0);
-                if (t_1.area() + t_2.area() + t_3.area()).fuzzy_eq(&self.area()) {
-                    return true;
-                } else {
-                    return false;
-                }
+                (t_1.area() + t_2.area() + t_3.area()).fuzzy_eq(&self.area())
             } else {
                 false
             }
@@ -71,29 +93,41 @@ impl PartialEq for Triangle {
 }
 
 impl Intersect for Triangle {
+    // Möller–Trumbore
     fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
         let mut intersection: Vec<Intersection> = vec![];
-        if let Some(norm) = &self.norm() {
-            if ray.direction.dot(norm).fuzzy_eq(&0.0) {
-                intersection
-            } else {
-                let a = norm.dot(&ray.origin.to_vector());
-                let d = -(norm.dot(&self.p_0.to_vector()));
-                let b = norm.dot(&ray.direction);
-                let t = -(a + d) / b;
-                let surface_point = ray.point_at(t);
-                let normal_v_res = self.norm_at(&surface_point);
-                if let (true, Ok(normal_v)) = (self.is_on(&surface_point), normal_v_res) {
-                    intersection.push(Intersection::new(t, ray.direction, surface_point, normal_v));
-                }
-                intersection
-            }
-        } else {
+        let edge_1 = Vector::from_points(&self.p_0, &self.p_1);
+        let edge_2 = Vector::from_points(&self.p_0, &self.p_2);
+        let dir_cross_e2 = ray.direction.cross(&edge_2);
+        let det = edge_1.dot(&dir_cross_e2);
+        // ray parallel to the triangle, or the triangle is degenerate
+        if det.abs() < EPSILON {
+            return intersection;
+        }
+        let inv_det = 1.0 / det;
+        let p0_to_origin = Vector::from_points(&self.p_0, &ray.origin);
+        let u = p0_to_origin.dot(&dir_cross_e2) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return intersection;
+        }
+        let origin_cross_e1 = p0_to_origin.cross(&edge_1);
+        let v = ray.direction.dot(&origin_cross_e1) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return intersection;
+        }
+        let t = edge_2.dot(&origin_cross_e1) * inv_det;
+        if let Some(normal_v) = self.norm() {
             intersection
+                .push(Intersection::new(t, ray.direction, ray.point_at(t), normal_v).with_uv(u, v));
         }
+        intersection
     }
 
     fn norm_at(&self, _: &Point) -> Result<Vector, String> {
         self.norm().ok_or("invalid triangle".to_string())
     }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::new(self.p_0, self.p_1).include(&self.p_2)
+    }
 }
@@ -0,0 +1,79 @@
+use crate::math::algebra::{point::Point, vector::Vector};
+
+use super::{
+    bounds::Aabb,
+    bvh::Bvh,
+    common::{Intersect, Intersection},
+    ray::Ray,
+    smooth_triangle::SmoothTriangle,
+};
+
+/// smooth shaded triangles sharing vertices, every 3 indices make a triangle
+pub struct TriangleMesh {
+    triangles: Vec<SmoothTriangle>,
+    bvh: Bvh,
+}
+
+impl TriangleMesh {
+    pub fn new(positions: &[Point], normals: &[Vector], indices: &[u32]) -> Result<Self, String> {
+        if positions.len() != normals.len() {
+            return Err("one normal for each vertex".to_string());
+        }
+        if !indices.len().is_multiple_of(3) {
+            return Err("indices are not triangles".to_string());
+        }
+        let vertex = |index: u32| {
+            let index = index as usize;
+            if index < positions.len() {
+                Ok((positions[index], normals[index]))
+            } else {
+                Err(format!("vertex index {index} out of range"))
+            }
+        };
+        let triangles = indices
+            .chunks(3)
+            .map(|triangle| {
+                Ok(SmoothTriangle::new(
+                    vertex(triangle[0])?,
+                    vertex(triangle[1])?,
+                    vertex(triangle[2])?,
+                ))
+            })
+            .collect::<Result<Vec<SmoothTriangle>, String>>()?;
+        let bounds: Vec<Aabb> = triangles.iter().map(|triangle| triangle.bounds()).collect();
+        Ok(Self {
+            bvh: Bvh::new(&bounds),
+            triangles,
+        })
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    pub fn get_triangles(&self) -> &[SmoothTriangle] {
+        &self.triangles
+    }
+}
+
+impl Intersect for TriangleMesh {
+    fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        self.bvh
+            .all(ray, |index| self.triangles[index].intersect(ray))
+            .into_iter()
+            .map(|(_, inter)| inter)
+            .collect()
+    }
+
+    fn norm_at(&self, point: &Point) -> Result<Vector, String> {
+        self.triangles
+            .iter()
+            .find(|triangle| triangle.get_triangle().is_on(point))
+            .ok_or("point not on surface".to_string())
+            .and_then(|triangle| triangle.norm_at(point))
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.bvh.get_bounds()
+    }
+}
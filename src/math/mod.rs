@@ -1,2 +1,2 @@
 pub mod algebra;
-pub mod geometry;
\ No newline at end of file
+pub mod geometry;
@@ -4,7 +4,6 @@ use crate::math::algebra::common::FuzzyEq;
 
 #[derive(Debug, Clone, Copy)]
 #[repr(C)]
-
 pub struct Color {
     r: f32,
     g: f32,
@@ -51,6 +50,10 @@ impl Color {
         (self.get_r(), self.get_g(), self.get_b())
     }
 
+    pub fn get_raw(&self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
     pub fn clamp(&self, low: f32, high: f32) -> Self {
         Self::rgb(
             self.r.max(low).min(high),
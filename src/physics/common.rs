@@ -1,17 +1,71 @@
 use crate::math::geometry::common::Intersection;
 
-use super::{color::Color, light::PointLight};
+use super::{color::Color, light::LightModel};
 
 pub trait Illuminated {
     ///all vector start from position in the calculation  
     fn lighting(
         &self,
         //incoming light source
-        light: &PointLight,
+        light: &dyn LightModel,
         intersection: &Intersection,
+        // fraction of the light reaching the point, 0.0 in shadow,
+        // only ambient light is not shadowed
+        visibility: f32,
     ) -> Color;
 
+    /// light given off by the surface, independent of light sources
+    fn emitted_light(&self) -> Color;
+
+    /// emitted light plus the light from each source, `visibility` gives the
+    /// fraction of a light reaching the point
+    fn shade(
+        &self,
+        lights: &[Box<dyn LightModel>],
+        intersection: &Intersection,
+        visibility: &mut dyn FnMut(&dyn LightModel) -> f32,
+    ) -> Color {
+        lights.iter().fold(self.emitted_light(), |color, light| {
+            let fraction = visibility(light.as_ref());
+            color + self.lighting(light.as_ref(), intersection, fraction)
+        })
+    }
+
     fn reflective(&self) -> bool;
 
     fn reflect_light(&self, color: &Color) -> Color;
+
+    fn transparent(&self) -> bool;
+
+    fn refractive_index(&self) -> f32;
+
+    /// light passing through the surface
+    fn refract_light(&self, color: &Color) -> Color;
+}
+
+/// Schlick's approximation of the Fresnel reflectance, cos_i is the cosine
+/// between eye vector and normal, 1.0 for total internal reflection
+pub fn schlick(cos_i: f32, n_1: f32, n_2: f32) -> f32 {
+    let mut cos = cos_i;
+    if n_1 > n_2 {
+        let sin2_t = (n_1 / n_2).powi(2) * (1.0 - cos_i.powi(2));
+        if sin2_t > 1.0 {
+            return 1.0;
+        }
+        cos = (1.0 - sin2_t).sqrt();
+    }
+    let r_0 = ((n_1 - n_2) / (n_1 + n_2)).powi(2);
+    r_0 + (1.0 - r_0) * (1.0 - cos).powi(5)
+}
+
+#[test]
+fn test_schlick() {
+    use crate::math::algebra::common::FuzzyEq;
+
+    // total internal reflection
+    assert_eq!(schlick(0.5, 1.5, 1.0), 1.0);
+    // perpendicular from air to glass
+    assert!(schlick(1.0, 1.0, 1.5).fuzzy_eq(&0.04));
+    // grazing
+    assert!(schlick(0.0, 1.0, 1.5) > 0.99);
 }
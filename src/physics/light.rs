@@ -1,10 +1,89 @@
-use crate::math::algebra::{common::FuzzyEq, point::Point};
+use crate::math::algebra::{
+    common::{Dimension4, FuzzyEq, deg_to_rad},
+    point::Point,
+    vector::Vector,
+};
 
 use super::color::Color;
-#[derive(Debug)]
+
+// light kind stored in the w component of the position in shader
+const POINT_KIND: f32 = 0.0;
+const DIRECTIONAL_KIND: f32 = 1.0;
+const SPOT_KIND: f32 = 2.0;
+
+// far enough to be outside of any scene, shadow rays of directional lights end there
+const DIRECTIONAL_DISTANCE: f32 = 1.0e6;
+
+/// light as seen from a point in the scene
+pub trait LightModel: Send + Sync {
+    /// unit vector from the point to the light, None at the light itself
+    fn direction_from(&self, point: &Point) -> Option<Vector>;
+
+    /// infinite for directional light
+    fn distance_from(&self, point: &Point) -> f32;
+
+    /// light arriving at the point, without shadows
+    fn radiance_at(&self, point: &Point) -> Color;
+
+    /// shadow rays for each shaded point, more than one for lights with an area
+    fn sample_count(&self) -> u32 {
+        1
+    }
+
+    /// position a shadow ray from the point aims at, (u, v) in the unit square
+    /// picks the position on lights with an area
+    fn sample_position(&self, point: &Point, u: f32, v: f32) -> Point;
+}
+
+/// falloff with distance d: 1 / (constant + linear * d + quadratic * d^2)
+#[derive(Debug, Clone, Copy)]
+pub struct Attenuation {
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl Attenuation {
+    pub fn new(constant: f32, linear: f32, quadratic: f32) -> Self {
+        Self {
+            constant,
+            linear,
+            quadratic,
+        }
+    }
+
+    pub fn factor_at(&self, distance: f32) -> f32 {
+        let denominator =
+            self.constant + self.linear * distance + self.quadratic * distance.powi(2);
+        if denominator > 0.0 {
+            1.0 / denominator
+        } else {
+            1.0
+        }
+    }
+}
+
+impl Default for Attenuation {
+    // no falloff
+    fn default() -> Self {
+        Self::new(1.0, 0.0, 0.0)
+    }
+}
+
+impl FuzzyEq for Attenuation {
+    fn fuzzy_eq(&self, other: &Self) -> bool {
+        self.constant.fuzzy_eq(&other.constant)
+            && self.linear.fuzzy_eq(&other.linear)
+            && self.quadratic.fuzzy_eq(&other.quadratic)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct PointLight {
     pub position: Point,
+    // color times intensity
     pub intensity: Color,
+    pub attenuation: Attenuation,
 }
 
 impl PointLight {
@@ -12,8 +91,37 @@ impl PointLight {
         Self {
             position,
             intensity,
+            attenuation: Attenuation::default(),
         }
     }
+
+    pub fn with_attenuation(mut self, attenuation: Attenuation) -> Self {
+        self.attenuation = attenuation;
+        self
+    }
+
+    /// intensity reaching the point
+    pub fn intensity_at(&self, point: &Point) -> Color {
+        self.intensity * self.attenuation.factor_at(self.position.distance(point))
+    }
+}
+
+impl LightModel for PointLight {
+    fn direction_from(&self, point: &Point) -> Option<Vector> {
+        Vector::from_points(point, &self.position).unit().ok()
+    }
+
+    fn distance_from(&self, point: &Point) -> f32 {
+        self.position.distance(point)
+    }
+
+    fn radiance_at(&self, point: &Point) -> Color {
+        self.intensity_at(point)
+    }
+
+    fn sample_position(&self, _: &Point, _: f32, _: f32) -> Point {
+        self.position
+    }
 }
 
 impl Default for PointLight {
@@ -24,7 +132,9 @@ impl Default for PointLight {
 
 impl FuzzyEq for PointLight {
     fn fuzzy_eq(&self, other: &Self) -> bool {
-        self.position.fuzzy_eq(&(other.position)) && self.intensity.fuzzy_eq(&(other.intensity))
+        self.position.fuzzy_eq(&(other.position))
+            && self.intensity.fuzzy_eq(&(other.intensity))
+            && self.attenuation.fuzzy_eq(&(other.attenuation))
     }
 }
 
@@ -33,3 +143,423 @@ impl PartialEq for PointLight {
         self.fuzzy_eq(other)
     }
 }
+
+/// parallel light from far away, e.g. sun light
+#[derive(Debug, Clone, Copy)]
+pub struct DirectionalLight {
+    // direction the light travels
+    pub direction: Vector,
+    pub intensity: Color,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vector, intensity: Color) -> Self {
+        Self {
+            direction,
+            intensity,
+        }
+    }
+}
+
+impl LightModel for DirectionalLight {
+    fn direction_from(&self, _: &Point) -> Option<Vector> {
+        (-self.direction).unit().ok()
+    }
+
+    fn distance_from(&self, _: &Point) -> f32 {
+        f32::INFINITY
+    }
+
+    fn radiance_at(&self, _: &Point) -> Color {
+        self.intensity
+    }
+
+    fn sample_position(&self, point: &Point, _: f32, _: f32) -> Point {
+        self.direction_from(point)
+            .map(|direction| *point + direction * DIRECTIONAL_DISTANCE)
+            .unwrap_or(*point)
+    }
+}
+
+/// point light limited to a cone, fading out between inner and outer angle
+#[derive(Debug, Clone, Copy)]
+pub struct SpotLight {
+    pub position: Point,
+    pub direction: Vector,
+    pub intensity: Color,
+    pub attenuation: Attenuation,
+    // half angle of the cone in degree
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+}
+
+impl SpotLight {
+    pub fn new(
+        position: Point,
+        direction: Vector,
+        intensity: Color,
+        inner_angle: f32,
+        outer_angle: f32,
+    ) -> Self {
+        Self {
+            position,
+            direction,
+            intensity,
+            attenuation: Attenuation::default(),
+            inner_angle,
+            outer_angle,
+        }
+    }
+
+    pub fn with_attenuation(mut self, attenuation: Attenuation) -> Self {
+        self.attenuation = attenuation;
+        self
+    }
+}
+
+impl LightModel for SpotLight {
+    fn direction_from(&self, point: &Point) -> Option<Vector> {
+        Vector::from_points(point, &self.position).unit().ok()
+    }
+
+    fn distance_from(&self, point: &Point) -> f32 {
+        self.position.distance(point)
+    }
+
+    // same smooth fade between the cone angles as in shader
+    fn radiance_at(&self, point: &Point) -> Color {
+        let (Some(to_light), Ok(direction)) = (self.direction_from(point), self.direction.unit())
+        else {
+            return Color::black();
+        };
+        let align = (-to_light).dot(&direction);
+        let (cos_inner, cos_outer) = (
+            deg_to_rad(self.inner_angle).cos(),
+            deg_to_rad(self.outer_angle).cos(),
+        );
+        let fade = ((align - cos_outer) / (cos_inner - cos_outer)).clamp(0.0, 1.0);
+        self.intensity
+            * self.attenuation.factor_at(self.distance_from(point))
+            * (fade * fade * (3.0 - 2.0 * fade))
+    }
+
+    fn sample_position(&self, _: &Point, _: f32, _: f32) -> Point {
+        self.position
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum AreaShape {
+    // corner and the two edges from it
+    Rectangle {
+        corner: Point,
+        edge_u: Vector,
+        edge_v: Vector,
+    },
+    Sphere {
+        center: Point,
+        radius: f32,
+    },
+}
+
+/// light from a surface, casts soft shadows in the CPU tracer
+#[derive(Debug, Clone, Copy)]
+pub struct AreaLight {
+    pub shape: AreaShape,
+    pub intensity: Color,
+    pub attenuation: Attenuation,
+    // shadow rays for each shaded point
+    samples: u32,
+}
+
+impl AreaLight {
+    pub fn rectangle(corner: Point, edge_u: Vector, edge_v: Vector, intensity: Color) -> Self {
+        Self::new(
+            AreaShape::Rectangle {
+                corner,
+                edge_u,
+                edge_v,
+            },
+            intensity,
+        )
+    }
+
+    pub fn sphere(center: Point, radius: f32, intensity: Color) -> Self {
+        Self::new(AreaShape::Sphere { center, radius }, intensity)
+    }
+
+    fn new(shape: AreaShape, intensity: Color) -> Self {
+        Self {
+            shape,
+            intensity,
+            attenuation: Attenuation::default(),
+            samples: 16,
+        }
+    }
+
+    pub fn with_attenuation(mut self, attenuation: Attenuation) -> Self {
+        self.attenuation = attenuation;
+        self
+    }
+
+    /// at least one
+    pub fn with_samples(mut self, samples: u32) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+
+    pub fn get_samples(&self) -> u32 {
+        self.samples
+    }
+
+    pub fn center(&self) -> Point {
+        match self.shape {
+            AreaShape::Rectangle {
+                corner,
+                edge_u,
+                edge_v,
+            } => corner + edge_u * 0.5 + edge_v * 0.5,
+            AreaShape::Sphere { center, .. } => center,
+        }
+    }
+
+    /// point on the light for (u, v) in the unit square,
+    /// a sphere is seen as the disk facing `toward`
+    pub fn point_at(&self, u: f32, v: f32, toward: &Point) -> Point {
+        match self.shape {
+            AreaShape::Rectangle {
+                corner,
+                edge_u,
+                edge_v,
+            } => corner + edge_u * u + edge_v * v,
+            AreaShape::Sphere { center, radius } => {
+                let Ok(facing) = Vector::from_points(&center, toward).unit() else {
+                    return center;
+                };
+                let helper = if facing.get_x().abs() < 0.9 {
+                    Vector::unit_x()
+                } else {
+                    Vector::unit_y()
+                };
+                let tangent = facing.cross(&helper).unit().unwrap_or(helper);
+                let bitangent = facing.cross(&tangent);
+                let (distance, angle) = (radius * u.sqrt(), 2.0 * std::f32::consts::PI * v);
+                center + tangent * (distance * angle.cos()) + bitangent * (distance * angle.sin())
+            }
+        }
+    }
+}
+
+// shaded as if all light comes from the center
+impl LightModel for AreaLight {
+    fn direction_from(&self, point: &Point) -> Option<Vector> {
+        Vector::from_points(point, &self.center()).unit().ok()
+    }
+
+    fn distance_from(&self, point: &Point) -> f32 {
+        self.center().distance(point)
+    }
+
+    fn radiance_at(&self, point: &Point) -> Color {
+        self.intensity * self.attenuation.factor_at(self.distance_from(point))
+    }
+
+    fn sample_count(&self) -> u32 {
+        self.samples
+    }
+
+    fn sample_position(&self, point: &Point, u: f32, v: f32) -> Point {
+        self.point_at(u, v, point)
+    }
+}
+
+/// light on the GPU scene
+#[derive(Debug, Clone, Copy)]
+pub enum Light {
+    Point(PointLight),
+    Directional(DirectionalLight),
+    Spot(SpotLight),
+}
+
+impl Light {
+    /// the light model for CPU shading
+    pub fn as_light(&self) -> &dyn LightModel {
+        match self {
+            Light::Point(light) => light,
+            Light::Directional(light) => light,
+            Light::Spot(light) => light,
+        }
+    }
+
+    /// storage layout in shader:
+    /// (position with kind in w, direction, intensity,
+    /// (constant, linear, quadratic, 0), (cos inner, cos outer, 0, 0))
+    pub fn get_raw(&self) -> [[f32; 4]; 5] {
+        match self {
+            Light::Point(light) => [
+                with_w(light.position.get_raw(), POINT_KIND),
+                [0.0; 4],
+                light.intensity.get_raw(),
+                attenuation_raw(&light.attenuation),
+                [0.0; 4],
+            ],
+            Light::Directional(light) => [
+                with_w([0.0; 4], DIRECTIONAL_KIND),
+                unit_raw(&light.direction),
+                light.intensity.get_raw(),
+                attenuation_raw(&Attenuation::default()),
+                [0.0; 4],
+            ],
+            Light::Spot(light) => [
+                with_w(light.position.get_raw(), SPOT_KIND),
+                unit_raw(&light.direction),
+                light.intensity.get_raw(),
+                attenuation_raw(&light.attenuation),
+                [
+                    deg_to_rad(light.inner_angle).cos(),
+                    deg_to_rad(light.outer_angle).cos(),
+                    0.0,
+                    0.0,
+                ],
+            ],
+        }
+    }
+}
+
+impl From<PointLight> for Light {
+    fn from(light: PointLight) -> Self {
+        Light::Point(light)
+    }
+}
+
+impl From<DirectionalLight> for Light {
+    fn from(light: DirectionalLight) -> Self {
+        Light::Directional(light)
+    }
+}
+
+impl From<SpotLight> for Light {
+    fn from(light: SpotLight) -> Self {
+        Light::Spot(light)
+    }
+}
+
+impl LightModel for Light {
+    fn direction_from(&self, point: &Point) -> Option<Vector> {
+        self.as_light().direction_from(point)
+    }
+
+    fn distance_from(&self, point: &Point) -> f32 {
+        self.as_light().distance_from(point)
+    }
+
+    fn radiance_at(&self, point: &Point) -> Color {
+        self.as_light().radiance_at(point)
+    }
+
+    fn sample_position(&self, point: &Point, u: f32, v: f32) -> Point {
+        self.as_light().sample_position(point, u, v)
+    }
+}
+
+fn with_w(mut raw: [f32; 4], w: f32) -> [f32; 4] {
+    raw[3] = w;
+    raw
+}
+
+fn unit_raw(v: &Vector) -> [f32; 4] {
+    v.unit().unwrap_or(-Vector::unit_y()).get_raw()
+}
+
+fn attenuation_raw(attenuation: &Attenuation) -> [f32; 4] {
+    [
+        attenuation.constant,
+        attenuation.linear,
+        attenuation.quadratic,
+        0.0,
+    ]
+}
+
+#[test]
+fn light_models() {
+    let point = Point::origin();
+    let lights: Vec<Box<dyn LightModel>> = vec![
+        Box::new(PointLight::new(Point::point(0.0, 2.0, 0.0), Color::white())),
+        Box::new(DirectionalLight::new(-Vector::unit_y(), Color::white())),
+        Box::new(SpotLight::new(
+            Point::point(0.0, 2.0, 0.0),
+            -Vector::unit_y(),
+            Color::white(),
+            20.0,
+            30.0,
+        )),
+        Box::new(
+            AreaLight::sphere(Point::point(0.0, 2.0, 0.0), 0.5, Color::white()).with_samples(9),
+        ),
+    ];
+    lights.iter().for_each(|light| {
+        assert_eq!(light.direction_from(&point), Some(Vector::unit_y()));
+        assert_eq!(light.radiance_at(&point), Color::white());
+    });
+    assert_eq!(lights[0].distance_from(&point), 2.0);
+    assert_eq!(lights[1].distance_from(&point), f32::INFINITY);
+    assert_eq!(lights[3].sample_count(), 9);
+    // rim of the disk facing the point
+    let rim = lights[3].sample_position(&point, 1.0, 0.3);
+    assert!((rim.get_y() - 2.0).abs() < 1e-5);
+    assert!((rim.distance(&Point::point(0.0, 2.0, 0.0)) - 0.5).abs() < 1e-5);
+
+    // outside of the spot cone
+    assert_eq!(
+        lights[2].radiance_at(&Point::point(2.0, 0.0, 0.0)),
+        Color::black()
+    );
+}
+
+#[test]
+fn light_source_raw() {
+    let position = Point::point(1.0, 2.0, 3.0);
+    let attenuation = Attenuation::new(1.0, 0.5, 0.25);
+
+    let point = Light::Point(
+        PointLight::new(position, Color::rgb(0.1, 0.2, 0.3)).with_attenuation(attenuation),
+    );
+    assert_eq!(
+        point.get_raw(),
+        [
+            [1.0, 2.0, 3.0, POINT_KIND],
+            [0.0; 4],
+            [0.1, 0.2, 0.3, 1.0],
+            [1.0, 0.5, 0.25, 0.0],
+            [0.0; 4],
+        ]
+    );
+
+    // direction is normalized, no falloff
+    let directional = Light::Directional(DirectionalLight::new(
+        Vector::vector(0.0, -2.0, 0.0),
+        Color::white(),
+    ));
+    assert_eq!(
+        directional.get_raw(),
+        [
+            [0.0, 0.0, 0.0, DIRECTIONAL_KIND],
+            [0.0, -1.0, 0.0, 0.0],
+            [1.0, 1.0, 1.0, 1.0],
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0; 4],
+        ]
+    );
+
+    let spot = Light::Spot(SpotLight::new(
+        position,
+        -Vector::unit_z(),
+        Color::white(),
+        0.0,
+        60.0,
+    ));
+    let raw = spot.get_raw();
+    assert_eq!(raw[0], [1.0, 2.0, 3.0, SPOT_KIND]);
+    assert_eq!(raw[1], [0.0, 0.0, -1.0, 0.0]);
+    assert!((raw[4][0] - 1.0).abs() < 1e-6 && (raw[4][1] - 0.5).abs() < 1e-6);
+}
@@ -0,0 +1,91 @@
+use crate::math::geometry::common::Intersection;
+
+use super::{
+    color::Color, common::Illuminated, light::LightModel, pattern::PATTERN_RAW_SIZE, pbr::Pbr,
+    phong::Phong,
+};
+
+// rows of the material uniform in shader
+pub const MATERIAL_RAW_SIZE: usize = 4 + PATTERN_RAW_SIZE;
+// shading model stored in the material uniform
+pub(super) const PHONG_MODEL: f32 = 0.0;
+pub(super) const PBR_MODEL: f32 = 1.0;
+
+/// surface model of an object, on the GPU and in the CPU tracers
+#[derive(Debug, Clone)]
+pub enum Material {
+    Phong(Phong),
+    Pbr(Pbr),
+}
+
+impl Material {
+    pub fn as_illuminated(&self) -> &dyn Illuminated {
+        match self {
+            Material::Phong(material) => material,
+            Material::Pbr(material) => material,
+        }
+    }
+
+    /// uniform layout in shader: (color, (ambient, diffuse, specular, shininess),
+    /// pattern, (model, metallic, roughness, 0), emission)
+    pub fn get_raw(&self) -> [[f32; 4]; MATERIAL_RAW_SIZE] {
+        match self {
+            Material::Phong(material) => material.get_raw(),
+            Material::Pbr(material) => material.get_raw(),
+        }
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material::Phong(Phong::default())
+    }
+}
+
+impl From<Phong> for Material {
+    fn from(material: Phong) -> Self {
+        Material::Phong(material)
+    }
+}
+
+impl From<Pbr> for Material {
+    fn from(material: Pbr) -> Self {
+        Material::Pbr(material)
+    }
+}
+
+impl Illuminated for Material {
+    fn lighting(
+        &self,
+        light: &dyn LightModel,
+        intersection: &Intersection,
+        visibility: f32,
+    ) -> Color {
+        self.as_illuminated()
+            .lighting(light, intersection, visibility)
+    }
+
+    fn emitted_light(&self) -> Color {
+        self.as_illuminated().emitted_light()
+    }
+
+    fn reflective(&self) -> bool {
+        self.as_illuminated().reflective()
+    }
+
+    fn reflect_light(&self, color: &Color) -> Color {
+        self.as_illuminated().reflect_light(color)
+    }
+
+    fn transparent(&self) -> bool {
+        self.as_illuminated().transparent()
+    }
+
+    fn refractive_index(&self) -> f32 {
+        self.as_illuminated().refractive_index()
+    }
+
+    fn refract_light(&self, color: &Color) -> Color {
+        self.as_illuminated().refract_light(color)
+    }
+}
@@ -1,4 +1,7 @@
 pub mod color;
 pub mod common;
 pub mod light;
+pub mod material;
+pub mod pattern;
+pub mod pbr;
 pub mod phong;
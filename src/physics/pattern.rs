@@ -0,0 +1,231 @@
+use crate::math::algebra::{matrix::Matrix, point::Point};
+
+use super::color::Color;
+
+// pattern kind in shader
+const SOLID_KIND: f32 = 0.0;
+const STRIPE_KIND: f32 = 1.0;
+const GRADIENT_KIND: f32 = 2.0;
+const RING_KIND: f32 = 3.0;
+const CHECKER_KIND: f32 = 4.0;
+const BLEND_KIND: f32 = 5.0;
+/// nodes of a pattern tree the shader can evaluate
+pub const MAX_PATTERN_NODES: usize = 16;
+// rows of one node in shader
+const PATTERN_NODE_SIZE: usize = 6;
+/// rows of a pattern in the material uniform
+pub const PATTERN_RAW_SIZE: usize = 1 + MAX_PATTERN_NODES * PATTERN_NODE_SIZE;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternKind {
+    Solid(Color),
+    // alternate every unit along x
+    Stripe(Box<Pattern>, Box<Pattern>),
+    // from the first to the second over every unit along x
+    Gradient(Box<Pattern>, Box<Pattern>),
+    // alternate every unit of distance from the y axis
+    Ring(Box<Pattern>, Box<Pattern>),
+    // alternate every unit cube
+    Checker(Box<Pattern>, Box<Pattern>),
+    // mix both, with the weight of the second
+    Blend(Box<Pattern>, Box<Pattern>, f32),
+}
+
+/// procedural surface color, sub patterns are evaluated in the space of the
+/// parent pattern, so patterns can be nested
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    kind: PatternKind,
+    transform: Matrix<4>,
+    // object space to pattern space
+    inverse: Matrix<4>,
+}
+
+impl Pattern {
+    fn new(kind: PatternKind) -> Self {
+        Self {
+            kind,
+            transform: Matrix::identity(),
+            inverse: Matrix::identity(),
+        }
+    }
+
+    pub fn solid(color: Color) -> Self {
+        Self::new(PatternKind::Solid(color))
+    }
+
+    pub fn stripe(a: impl Into<Pattern>, b: impl Into<Pattern>) -> Self {
+        Self::new(PatternKind::Stripe(Box::new(a.into()), Box::new(b.into())))
+    }
+
+    pub fn gradient(a: impl Into<Pattern>, b: impl Into<Pattern>) -> Self {
+        Self::new(PatternKind::Gradient(
+            Box::new(a.into()),
+            Box::new(b.into()),
+        ))
+    }
+
+    pub fn ring(a: impl Into<Pattern>, b: impl Into<Pattern>) -> Self {
+        Self::new(PatternKind::Ring(Box::new(a.into()), Box::new(b.into())))
+    }
+
+    pub fn checker(a: impl Into<Pattern>, b: impl Into<Pattern>) -> Self {
+        Self::new(PatternKind::Checker(Box::new(a.into()), Box::new(b.into())))
+    }
+
+    /// weight of b in [0, 1]
+    pub fn blend(a: impl Into<Pattern>, b: impl Into<Pattern>, weight: f32) -> Self {
+        Self::new(PatternKind::Blend(
+            Box::new(a.into()),
+            Box::new(b.into()),
+            weight.clamp(0.0, 1.0),
+        ))
+    }
+
+    /// transform from pattern space to the space of the parent
+    pub fn with_transform(mut self, transform: Matrix<4>) -> Result<Self, String> {
+        self.inverse = transform.inverse()?;
+        self.transform = transform;
+        Ok(self)
+    }
+
+    pub fn get_kind(&self) -> &PatternKind {
+        &self.kind
+    }
+
+    pub fn get_transform(&self) -> Matrix<4> {
+        self.transform
+    }
+
+    /// color at a point in the space of the parent, object space for the top pattern
+    pub fn color_at(&self, point: &Point) -> Color {
+        let local = self.inverse * *point;
+        let (x, y, z) = local.get_value();
+        match &self.kind {
+            PatternKind::Solid(color) => *color,
+            PatternKind::Stripe(a, b) => alternate(x.floor(), a, b, &local),
+            PatternKind::Gradient(a, b) => {
+                let fraction = x - x.floor();
+                a.color_at(&local) * (1.0 - fraction) + b.color_at(&local) * fraction
+            }
+            PatternKind::Ring(a, b) => {
+                alternate((x.powi(2) + z.powi(2)).sqrt().floor(), a, b, &local)
+            }
+            PatternKind::Checker(a, b) => {
+                alternate(x.floor() + y.floor() + z.floor(), a, b, &local)
+            }
+            PatternKind::Blend(a, b, weight) => {
+                a.color_at(&local) * (1.0 - weight) + b.color_at(&local) * *weight
+            }
+        }
+    }
+
+    /// uniform layout in shader: ((node count, 0, 0, 0), nodes), the nodes are
+    /// in pre-order, each ((kind, blend weight, index of a, index of b), color,
+    /// inverse transform), Err if the tree has too many nodes for the shader
+    pub fn get_raw(&self) -> Result<[[f32; 4]; PATTERN_RAW_SIZE], String> {
+        let mut nodes = vec![];
+        self.flatten(&mut nodes);
+        if nodes.len() > MAX_PATTERN_NODES {
+            return Err(format!(
+                "pattern with {} nodes, the shader takes at most {MAX_PATTERN_NODES}",
+                nodes.len()
+            ));
+        }
+        let mut raw = [[0.0; 4]; PATTERN_RAW_SIZE];
+        raw[0] = [nodes.len() as f32, 0.0, 0.0, 0.0];
+        nodes.iter().enumerate().for_each(|(index, node)| {
+            let start = 1 + index * PATTERN_NODE_SIZE;
+            raw[start..start + PATTERN_NODE_SIZE].copy_from_slice(node);
+        });
+        Ok(raw)
+    }
+
+    // append the node and its sub patterns, returns the index of the node
+    fn flatten(&self, nodes: &mut Vec<[[f32; 4]; PATTERN_NODE_SIZE]>) -> usize {
+        let index = nodes.len();
+        nodes.push([[0.0; 4]; PATTERN_NODE_SIZE]);
+        let (kind, weight, color, children) = match &self.kind {
+            PatternKind::Solid(color) => (SOLID_KIND, 0.0, *color, None),
+            PatternKind::Stripe(a, b) => (STRIPE_KIND, 0.0, Color::black(), Some((a, b))),
+            PatternKind::Gradient(a, b) => (GRADIENT_KIND, 0.0, Color::black(), Some((a, b))),
+            PatternKind::Ring(a, b) => (RING_KIND, 0.0, Color::black(), Some((a, b))),
+            PatternKind::Checker(a, b) => (CHECKER_KIND, 0.0, Color::black(), Some((a, b))),
+            PatternKind::Blend(a, b, weight) => (BLEND_KIND, *weight, Color::black(), Some((a, b))),
+        };
+        let (a, b) = children
+            .map(|(a, b)| (a.flatten(nodes), b.flatten(nodes)))
+            .unwrap_or((index, index));
+        let inverse = self.inverse.get_raw();
+        nodes[index] = [
+            [kind, weight, a as f32, b as f32],
+            color.get_raw(),
+            inverse[0],
+            inverse[1],
+            inverse[2],
+            inverse[3],
+        ];
+        index
+    }
+}
+
+impl From<Color> for Pattern {
+    fn from(color: Color) -> Self {
+        Self::solid(color)
+    }
+}
+
+// first pattern on even cells
+fn alternate(cell: f32, a: &Pattern, b: &Pattern, point: &Point) -> Color {
+    if cell.rem_euclid(2.0) < 1.0 {
+        a.color_at(point)
+    } else {
+        b.color_at(point)
+    }
+}
+
+#[test]
+fn test_pattern() {
+    let (white, black) = (Color::white(), Color::black());
+
+    let stripe = Pattern::stripe(white, black);
+    assert_eq!(stripe.color_at(&Point::point(0.9, 5.0, 0.0)), white);
+    assert_eq!(stripe.color_at(&Point::point(1.0, 0.0, 0.0)), black);
+    assert_eq!(stripe.color_at(&Point::point(-0.1, 0.0, 0.0)), black);
+
+    let gradient = Pattern::gradient(white, black);
+    assert_eq!(
+        gradient.color_at(&Point::point(0.25, 0.0, 0.0)),
+        Color::rgb(0.75, 0.75, 0.75)
+    );
+
+    let ring = Pattern::ring(white, black);
+    assert_eq!(ring.color_at(&Point::point(0.708, 0.0, 0.708)), black);
+
+    let checker = Pattern::checker(white, black);
+    assert_eq!(checker.color_at(&Point::point(0.0, 0.0, 1.01)), black);
+
+    // scaled stripe
+    let stripe = stripe.with_transform(Matrix::scale(2.0, 1.0, 1.0)).unwrap();
+    assert_eq!(stripe.color_at(&Point::point(1.5, 0.0, 0.0)), white);
+
+    // nested patterns in the checker cells
+    let nested = Pattern::checker(Pattern::stripe(white, black), Color::rgb(1.0, 0.0, 0.0));
+    assert_eq!(nested.color_at(&Point::point(1.5, 0.0, 1.5)), black);
+    assert_eq!(
+        nested.color_at(&Point::point(1.5, 0.0, 0.5)),
+        Color::rgb(1.0, 0.0, 0.0)
+    );
+
+    // flattened for the shader in pre-order
+    let raw = nested.get_raw().unwrap();
+    assert_eq!(raw[0][0], 5.0);
+    assert_eq!(raw[1], [CHECKER_KIND, 0.0, 1.0, 4.0]);
+    assert_eq!(raw[7], [STRIPE_KIND, 0.0, 2.0, 3.0]);
+    assert_eq!(raw[26], Color::rgb(1.0, 0.0, 0.0).get_raw());
+    let deep = (0..MAX_PATTERN_NODES / 2).fold(Pattern::solid(white), |pattern, _| {
+        Pattern::stripe(pattern, black)
+    });
+    assert!(deep.get_raw().is_err());
+    assert!(super::phong::Phong::default().with_pattern(deep).is_err());
+}
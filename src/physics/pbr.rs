@@ -0,0 +1,260 @@
+use std::f32::consts::PI;
+
+use crate::math::{
+    algebra::{common::FuzzyEq, vector::Vector},
+    geometry::common::Intersection,
+};
+
+use super::{
+    color::Color,
+    common::Illuminated,
+    light::LightModel,
+    material::{MATERIAL_RAW_SIZE, PBR_MODEL},
+    pattern::{PATTERN_RAW_SIZE, Pattern},
+};
+
+// smoother surfaces make the highlight a singular point
+const MIN_ROUGHNESS: f32 = 0.04;
+// reflectance at normal incidence of non metals
+const DIELECTRIC_REFLECTANCE: f32 = 0.04;
+
+/// metallic-roughness material with Cook-Torrance GGX specular,
+/// as used by PBR asset pipelines
+#[derive(Debug, Clone, Copy)]
+pub struct Pbr {
+    base_color: Color,
+    metallic: f32,
+    roughness: f32,
+    emissive: Color,
+}
+
+impl Default for Pbr {
+    fn default() -> Self {
+        Self::new(Color::white(), 0.0, 0.5)
+    }
+}
+
+impl Pbr {
+    /// metallic and roughness from 0.0 to 1.0
+    pub fn new(base_color: Color, metallic: f32, roughness: f32) -> Self {
+        Self {
+            base_color,
+            metallic: metallic.clamp(0.0, 1.0),
+            roughness: roughness.clamp(0.0, 1.0),
+            emissive: Color::black(),
+        }
+    }
+
+    pub fn with_base_color(mut self, base_color: Color) -> Self {
+        self.base_color = base_color;
+        self
+    }
+
+    pub fn with_metallic(mut self, metallic: f32) -> Self {
+        self.metallic = metallic.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_roughness(mut self, roughness: f32) -> Self {
+        self.roughness = roughness.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_emissive(mut self, emissive: Color) -> Self {
+        self.emissive = emissive;
+        self
+    }
+
+    pub fn get_base_color(&self) -> Color {
+        self.base_color
+    }
+
+    pub fn get_metallic(&self) -> f32 {
+        self.metallic
+    }
+
+    pub fn get_roughness(&self) -> f32 {
+        self.roughness
+    }
+
+    pub fn get_emissive(&self) -> Color {
+        self.emissive
+    }
+
+    /// light leaving to the eye from light arriving along light_v,
+    /// all vectors are unit and start from the surface
+    pub fn reflected_radiance(
+        &self,
+        normal: &Vector,
+        eye_v: &Vector,
+        light_v: &Vector,
+        radiance: Color,
+    ) -> Color {
+        let n_dot_l = normal.dot(light_v);
+        if n_dot_l <= 0.0 {
+            return Color::black();
+        }
+        let Ok(half_v) = (*light_v + *eye_v).unit() else {
+            return Color::black();
+        };
+        let n_dot_v = normal.dot(eye_v).max(1e-4);
+        let n_dot_h = normal.dot(&half_v).max(0.0);
+        let v_dot_h = eye_v.dot(&half_v).max(0.0);
+
+        let alpha_2 = self.get_alpha().powi(2);
+        // GGX normal distribution
+        let distribution = alpha_2 / (PI * (n_dot_h.powi(2) * (alpha_2 - 1.0) + 1.0).powi(2));
+        let fresnel = self.fresnel(v_dot_h);
+        let specular = fresnel
+            * (distribution * self.geometry(n_dot_v, n_dot_l)
+                / (4.0 * n_dot_v * n_dot_l).max(1e-4));
+        // light not reflected at the surface is diffused, except by metals
+        let diffuse =
+            (Color::white() + fresnel * -1.0) * self.base_color * ((1.0 - self.metallic) / PI);
+        (diffuse + specular) * radiance * n_dot_l
+    }
+
+    /// width of the GGX distribution, squared roughness
+    pub fn get_alpha(&self) -> f32 {
+        self.roughness.max(MIN_ROUGHNESS).powi(2)
+    }
+
+    /// specular share of the light arriving along light_v, when light_v is
+    /// the eye vector reflected about a half vector sampled with density D * cos,
+    /// i.e. D * G * F / (4 * n.v * n.l) * n.l divided by the pdf
+    pub fn specular_weight(&self, normal: &Vector, eye_v: &Vector, light_v: &Vector) -> Color {
+        let n_dot_l = normal.dot(light_v);
+        let Ok(half_v) = (*light_v + *eye_v).unit() else {
+            return Color::black();
+        };
+        if n_dot_l <= 0.0 {
+            return Color::black();
+        }
+        let n_dot_v = normal.dot(eye_v).max(1e-4);
+        let n_dot_h = normal.dot(&half_v).max(1e-4);
+        let v_dot_h = eye_v.dot(&half_v).max(0.0);
+        self.fresnel(v_dot_h) * (self.geometry(n_dot_v, n_dot_l) * v_dot_h / (n_dot_v * n_dot_h))
+    }
+
+    /// diffuse share of the light arriving along light_v, when light_v is
+    /// sampled with density cos / PI
+    pub fn diffuse_weight(&self, normal: &Vector, eye_v: &Vector, light_v: &Vector) -> Color {
+        let Ok(half_v) = (*light_v + *eye_v).unit() else {
+            return Color::black();
+        };
+        if normal.dot(light_v) <= 0.0 {
+            return Color::black();
+        }
+        let fresnel = self.fresnel(eye_v.dot(&half_v).max(0.0));
+        (Color::white() + fresnel * -1.0) * self.base_color * (1.0 - self.metallic)
+    }
+
+    // Schlick Fresnel, metals reflect their base color
+    fn fresnel(&self, v_dot_h: f32) -> Color {
+        let f_0 = Color::rgb(
+            DIELECTRIC_REFLECTANCE,
+            DIELECTRIC_REFLECTANCE,
+            DIELECTRIC_REFLECTANCE,
+        ) * (1.0 - self.metallic)
+            + self.base_color * self.metallic;
+        f_0 + (Color::white() + f_0 * -1.0) * (1.0 - v_dot_h).powi(5)
+    }
+
+    // Smith shadowing with Schlick-GGX for direct light
+    fn geometry(&self, n_dot_v: f32, n_dot_l: f32) -> f32 {
+        let k = (self.roughness.max(MIN_ROUGHNESS) + 1.0).powi(2) / 8.0;
+        n_dot_v / (n_dot_v * (1.0 - k) + k) * n_dot_l / (n_dot_l * (1.0 - k) + k)
+    }
+
+    /// uniform layout in shader, same as `Phong::get_raw` with base color in
+    /// place of the color and the pattern
+    pub fn get_raw(&self) -> [[f32; 4]; MATERIAL_RAW_SIZE] {
+        let mut raw = [[0.0; 4]; MATERIAL_RAW_SIZE];
+        raw[0] = self.base_color.get_raw();
+        // a solid color always fits
+        let pattern = Pattern::solid(self.base_color).get_raw().unwrap();
+        raw[2..2 + PATTERN_RAW_SIZE].copy_from_slice(&pattern);
+        raw[MATERIAL_RAW_SIZE - 2] = [PBR_MODEL, self.metallic, self.roughness, 0.0];
+        raw[MATERIAL_RAW_SIZE - 1] = self.emissive.get_raw();
+        raw
+    }
+}
+
+impl FuzzyEq for Pbr {
+    fn fuzzy_eq(&self, other: &Self) -> bool {
+        self.base_color.fuzzy_eq(&other.base_color)
+            && self.metallic.fuzzy_eq(&other.metallic)
+            && self.roughness.fuzzy_eq(&other.roughness)
+            && self.emissive.fuzzy_eq(&other.emissive)
+    }
+}
+
+// direct light only, without mirror reflection or refraction
+impl Illuminated for Pbr {
+    fn lighting(
+        &self,
+        light: &dyn LightModel,
+        intersection: &Intersection,
+        visibility: f32,
+    ) -> Color {
+        let point = intersection.get_surface_point();
+        match light.direction_from(&point) {
+            None => Color::black(),
+            Some(light_v) => self.reflected_radiance(
+                &intersection.get_shading_normal(),
+                &intersection.get_eye_v(),
+                &light_v,
+                light.radiance_at(&point) * visibility.clamp(0.0, 1.0),
+            ),
+        }
+    }
+
+    fn emitted_light(&self) -> Color {
+        self.emissive
+    }
+
+    fn reflective(&self) -> bool {
+        false
+    }
+
+    fn reflect_light(&self, _: &Color) -> Color {
+        Color::black()
+    }
+
+    fn transparent(&self) -> bool {
+        false
+    }
+
+    fn refractive_index(&self) -> f32 {
+        1.0
+    }
+
+    fn refract_light(&self, _: &Color) -> Color {
+        Color::black()
+    }
+}
+
+#[test]
+fn test_reflected_radiance() {
+    let normal = Vector::unit_y();
+    let white = Color::white();
+
+    // rough white non metal from straight above is nearly lambertian
+    let rough = Pbr::new(white, 0.0, 1.0);
+    let color = rough.reflected_radiance(&normal, &normal, &normal, white);
+    assert!((color.get_r() - 0.96 / PI).abs() < 0.02);
+
+    // no light from below the surface
+    let color = rough.reflected_radiance(&normal, &normal, &-normal, white);
+    assert_eq!(color, Color::black());
+
+    // smooth metal has a sharp highlight in the mirror direction only
+    let metal = Pbr::new(Color::rgb(1.0, 0.5, 0.0), 1.0, 0.1);
+    let light_v = Vector::vector(1.0, 1.0, 0.0).unit().unwrap();
+    let mirror = Vector::vector(-1.0, 1.0, 0.0).unit().unwrap();
+    let highlight = metal.reflected_radiance(&normal, &mirror, &light_v, white);
+    let off = metal.reflected_radiance(&normal, &normal, &light_v, white);
+    assert!(highlight.get_r() > 10.0 * off.get_r());
+    // tinted by the base color
+    assert!(highlight.get_b() < 0.1 * highlight.get_r());
+}
@@ -1,28 +1,45 @@
 use crate::math::{
-    algebra::{common::FuzzyEq, vector::Vector},
+    algebra::{common::FuzzyEq, point::Point},
     geometry::common::Intersection,
 };
 
-use super::{color::Color, common::Illuminated, light::PointLight};
+use super::{
+    color::Color,
+    common::Illuminated,
+    light::LightModel,
+    material::{MATERIAL_RAW_SIZE, PHONG_MODEL},
+    pattern::{PATTERN_RAW_SIZE, Pattern},
+};
 
+#[derive(Debug, Clone)]
 pub struct Phong {
     pub color: Color,
+    // replaces color if set
+    pattern: Option<Pattern>,
     ambient: f32,
     diffuse: f32,
     specular: f32,
     shininess: f32,
     reflectiveness: f32,
+    transparency: f32,
+    refractive_index: f32,
+    // light given off by the surface itself
+    emission: Color,
 }
 
 impl Default for Phong {
     fn default() -> Self {
         Phong {
+            pattern: None,
             color: Color::rgb(1.0, 1.0, 1.0),
             ambient: 0.1,
             diffuse: 0.9,
             specular: 0.9,
             shininess: 200.0,
             reflectiveness: 0.0,
+            transparency: 0.0,
+            refractive_index: 1.0,
+            emission: Color::black(),
         }
     }
 }
@@ -30,12 +47,46 @@ impl Default for Phong {
 impl Phong {
     pub fn metal() -> Self {
         Phong {
+            pattern: None,
             color: Color::rgb(0.0, 0.0, 0.0),
             ambient: 0.0,
             diffuse: 0.4,
             specular: 0.9,
             shininess: 200.0,
             reflectiveness: 1.0,
+            transparency: 0.0,
+            refractive_index: 1.0,
+            emission: Color::black(),
+        }
+    }
+
+    pub fn glass() -> Self {
+        Phong {
+            pattern: None,
+            color: Color::rgb(0.0, 0.0, 0.0),
+            ambient: 0.0,
+            diffuse: 0.1,
+            specular: 1.0,
+            shininess: 300.0,
+            reflectiveness: 0.0,
+            transparency: 1.0,
+            refractive_index: 1.5,
+            emission: Color::black(),
+        }
+    }
+
+    pub fn water() -> Self {
+        Phong {
+            pattern: None,
+            color: Color::rgb(0.0, 0.1, 0.2),
+            ambient: 0.0,
+            diffuse: 0.1,
+            specular: 0.8,
+            shininess: 300.0,
+            reflectiveness: 0.0,
+            transparency: 0.9,
+            refractive_index: 1.333,
+            emission: Color::black(),
         }
     }
 
@@ -44,6 +95,26 @@ impl Phong {
         self
     }
 
+    /// Err if the pattern has more than `MAX_PATTERN_NODES` nodes,
+    /// the GPU could not render it
+    pub fn with_pattern(mut self, pattern: Pattern) -> Result<Self, String> {
+        pattern.get_raw()?;
+        self.pattern = Some(pattern);
+        Ok(self)
+    }
+
+    pub fn get_pattern(&self) -> Option<&Pattern> {
+        self.pattern.as_ref()
+    }
+
+    /// color at a point in object space
+    pub fn color_at(&self, object_point: &Point) -> Color {
+        self.pattern
+            .as_ref()
+            .map(|pattern| pattern.color_at(object_point))
+            .unwrap_or(self.color)
+    }
+
     pub fn with_ambient(mut self, ambient: f32) -> Self {
         self.ambient = ambient;
         self
@@ -68,6 +139,61 @@ impl Phong {
         self.reflectiveness = reflectiveness;
         self
     }
+
+    pub fn with_transparency(mut self, transparency: f32) -> Self {
+        self.transparency = transparency;
+        self
+    }
+
+    /// 1.0 for vacuum, about 1.5 for glass
+    pub fn with_refractive_index(mut self, refractive_index: f32) -> Self {
+        self.refractive_index = refractive_index;
+        self
+    }
+
+    pub fn with_emission(mut self, emission: Color) -> Self {
+        self.emission = emission;
+        self
+    }
+
+    pub fn get_diffuse(&self) -> f32 {
+        self.diffuse
+    }
+
+    pub fn get_reflectiveness(&self) -> f32 {
+        self.reflectiveness
+    }
+
+    pub fn get_transparency(&self) -> f32 {
+        self.transparency
+    }
+
+    pub fn get_refractive_index(&self) -> f32 {
+        self.refractive_index
+    }
+
+    pub fn get_emission(&self) -> Color {
+        self.emission
+    }
+
+    /// uniform layout in shader: (color, (ambient, diffuse, specular, shininess),
+    /// pattern, (model, 0, 0, 0), emission), the pattern is solid color without pattern
+    pub fn get_raw(&self) -> [[f32; 4]; MATERIAL_RAW_SIZE] {
+        // `with_pattern` only takes patterns that fit, a solid color always fits
+        let pattern = self
+            .pattern
+            .clone()
+            .unwrap_or(Pattern::solid(self.color))
+            .get_raw()
+            .unwrap();
+        let mut raw = [[0.0; 4]; MATERIAL_RAW_SIZE];
+        raw[0] = self.color.get_raw();
+        raw[1] = [self.ambient, self.diffuse, self.specular, self.shininess];
+        raw[2..2 + PATTERN_RAW_SIZE].copy_from_slice(&pattern);
+        raw[MATERIAL_RAW_SIZE - 2] = [PHONG_MODEL, 0.0, 0.0, 0.0];
+        raw[MATERIAL_RAW_SIZE - 1] = self.emission.get_raw();
+        raw
+    }
 }
 
 impl FuzzyEq for Phong {
@@ -77,38 +203,51 @@ impl FuzzyEq for Phong {
             && self.diffuse.fuzzy_eq(&other.diffuse)
             && self.specular.fuzzy_eq(&other.specular)
             && self.shininess.fuzzy_eq(&other.shininess)
+            && self.reflectiveness.fuzzy_eq(&other.reflectiveness)
+            && self.transparency.fuzzy_eq(&other.transparency)
+            && self.refractive_index.fuzzy_eq(&other.refractive_index)
+            && self.emission.fuzzy_eq(&other.emission)
+            && self.pattern == other.pattern
     }
 }
 
 impl Illuminated for Phong {
-    fn lighting(&self, light: &PointLight, intersection: &Intersection) -> Color {
-        let effective_color = self.color * light.intensity;
+    fn lighting(
+        &self,
+        light: &dyn LightModel,
+        intersection: &Intersection,
+        visibility: f32,
+    ) -> Color {
+        let intensity = light.radiance_at(&intersection.get_surface_point());
+        let effective_color = self.color_at(&intersection.get_object_point()) * intensity;
         let ambient_color = effective_color * self.ambient;
-        let light_v_try =
-            Vector::from_points(&intersection.get_surface_point(), &light.position).unit();
-        match light_v_try {
-            Err(_) => Color::black() + Color::black() + Color::black(),
-            Ok(light_v) => {
-                let light_normal = light_v.dot(&intersection.get_normal());
+        if visibility <= 0.0 {
+            return ambient_color;
+        }
+        // diffuse and specular only from the visible part of the light
+        let intensity = intensity * visibility.min(1.0);
+        let effective_color = effective_color * visibility.min(1.0);
+        match light.direction_from(&intersection.get_surface_point()) {
+            None => Color::black() + Color::black() + Color::black(),
+            Some(light_v) => {
+                let light_normal = light_v.dot(&intersection.get_shading_normal());
                 if light_normal < 0.0 {
                     //light on the other side of the surface
-                    return ambient_color + Color::black() + Color::black();
+                    ambient_color + Color::black() + Color::black()
                 } else {
                     //light on the same side of the surface
                     let diffuse_color = effective_color * self.diffuse * light_normal;
-                    let reflect_v_try = light_v.reflect(&intersection.get_normal());
+                    let reflect_v_try = light_v.reflect(&intersection.get_shading_normal());
                     match reflect_v_try {
                         Err(_) => ambient_color + diffuse_color + Color::black(),
                         Ok(reflect_v) => {
                             let reflect_eye = reflect_v.dot(&intersection.get_eye_v());
                             if reflect_eye <= 0.0 {
-                                return ambient_color + diffuse_color + Color::black();
+                                ambient_color + diffuse_color + Color::black()
                             } else {
-                                return ambient_color
+                                ambient_color
                                     + diffuse_color
-                                    + light.intensity
-                                        * self.specular
-                                        * reflect_eye.powf(self.shininess);
+                                    + intensity * self.specular * reflect_eye.powf(self.shininess)
                             }
                         }
                     }
@@ -117,11 +256,27 @@ impl Illuminated for Phong {
         }
     }
 
+    fn emitted_light(&self) -> Color {
+        self.emission
+    }
+
     fn reflective(&self) -> bool {
-        self.reflectiveness.fuzzy_eq(&1.0)
+        self.reflectiveness > 0.0
     }
 
     fn reflect_light(&self, color: &Color) -> Color {
         *color * self.reflectiveness
     }
+
+    fn transparent(&self) -> bool {
+        self.transparency > 0.0
+    }
+
+    fn refractive_index(&self) -> f32 {
+        self.refractive_index
+    }
+
+    fn refract_light(&self, color: &Color) -> Color {
+        *color * self.transparency
+    }
 }
@@ -5,10 +5,10 @@ use wgpu::{
     PipelineLayoutDescriptor, PrimitiveState, PrimitiveTopology, RenderPass,
     RenderPassColorAttachment, RenderPassDepthStencilAttachment, RenderPassDescriptor,
     RenderPipeline, RenderPipelineDescriptor, ShaderModuleDescriptor, ShaderSource, ShaderStages,
-    StencilState, StoreOp, SurfaceConfiguration, SurfaceTexture, TextureDescriptor,
-    TextureDimension, TextureFormat, TextureUsages, TextureView, TextureViewDescriptor,
-    VertexState, wgc::id::markers::RenderPassEncoder,
+    StencilState, StoreOp, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+    TextureView, TextureViewDescriptor, VertexState,
 };
+use winit::dpi::PhysicalSize;
 
 use crate::render::web_gpu::create_vertex_buffer_layout;
 
@@ -16,14 +16,41 @@ const DEFAULT_MULTI_SAMPLE: u32 = 4;
 
 pub struct RenderConfig {
     pub render_pipeline: RenderPipeline,
-    pub bind_group_layout: [BindGroupLayout; 2],
+    pub bind_group_layout: [BindGroupLayout; 3],
     depth_view: TextureView,
     multi_sample_view: TextureView,
 }
 
 impl RenderConfig {
-    pub fn new(device: &Device, surface_config: &SurfaceConfiguration) -> Self {
+    pub fn new(device: &Device, format: TextureFormat, size: PhysicalSize<u32>) -> Self {
         let scene_bind_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::VERTEX_FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // lights
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let model_bind_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
             label: None,
             entries: &[BindGroupLayoutEntry {
                 binding: 0,
@@ -37,11 +64,11 @@ impl RenderConfig {
             }],
         });
 
-        let model_bind_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        let material_bind_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
             label: None,
             entries: &[BindGroupLayoutEntry {
                 binding: 0,
-                visibility: ShaderStages::VERTEX_FRAGMENT,
+                visibility: ShaderStages::FRAGMENT,
                 ty: BindingType::Buffer {
                     ty: BufferBindingType::Uniform,
                     has_dynamic_offset: false,
@@ -53,25 +80,33 @@ impl RenderConfig {
 
         let render_pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
             label: None,
-            bind_group_layouts: &[&scene_bind_layout, &model_bind_layout],
+            bind_group_layouts: &[
+                &scene_bind_layout,
+                &model_bind_layout,
+                &material_bind_layout,
+            ],
             push_constant_ranges: &[],
         });
 
-        let render_pipeline =
-            create_render_pipeline(device, surface_config.format, &render_pipeline_layout);
+        let render_pipeline = create_render_pipeline(device, format, &render_pipeline_layout);
 
-        let (depth_view, multi_sample_view) = create_render_view(device, surface_config);
+        let (depth_view, multi_sample_view) = create_render_view(device, format, size);
 
         Self {
             render_pipeline,
-            bind_group_layout: [scene_bind_layout, model_bind_layout],
+            bind_group_layout: [scene_bind_layout, model_bind_layout, material_bind_layout],
             depth_view,
             multi_sample_view,
         }
     }
 
-    pub fn update_render_view(&mut self, device: &Device, surface_config: &SurfaceConfiguration) {
-        let (depth_view, multi_sample_view) = create_render_view(device, surface_config);
+    pub fn update_render_view(
+        &mut self,
+        device: &Device,
+        format: TextureFormat,
+        size: PhysicalSize<u32>,
+    ) {
+        let (depth_view, multi_sample_view) = create_render_view(device, format, size);
         self.depth_view = depth_view;
         self.multi_sample_view = multi_sample_view;
     }
@@ -79,17 +114,13 @@ impl RenderConfig {
     pub fn create_render_pass<'a, 'b: 'a>(
         &'a self,
         encoder: &'b mut CommandEncoder,
-        surface_texture: &SurfaceTexture,
+        target_view: &TextureView,
     ) -> RenderPass<'a> {
         let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
             label: None,
             color_attachments: &[Some(RenderPassColorAttachment {
                 view: &self.multi_sample_view,
-                resolve_target: Some(
-                    &surface_texture
-                        .texture
-                        .create_view(&TextureViewDescriptor::default()),
-                ),
+                resolve_target: Some(target_view),
                 ops: Operations {
                     load: LoadOp::Clear(Color::BLACK),
                     store: StoreOp::Store,
@@ -110,21 +141,22 @@ impl RenderConfig {
             occlusion_query_set: None,
         });
         render_pass.set_pipeline(&self.render_pipeline);
-        return render_pass;
+        render_pass
     }
 }
 
 pub fn create_render_view(
     device: &Device,
-    surface_config: &SurfaceConfiguration,
+    format: TextureFormat,
+    size: PhysicalSize<u32>,
 ) -> (TextureView, TextureView) {
     (
         device
             .create_texture(&TextureDescriptor {
                 label: None,
                 size: Extent3d {
-                    width: surface_config.width.max(1),
-                    height: surface_config.height.max(1),
+                    width: size.width.max(1),
+                    height: size.height.max(1),
                     depth_or_array_layers: 1,
                 },
                 mip_level_count: 1,
@@ -139,16 +171,16 @@ pub fn create_render_view(
             .create_texture(&TextureDescriptor {
                 label: None,
                 size: Extent3d {
-                    width: surface_config.width.max(1),
-                    height: surface_config.height.max(1),
+                    width: size.width.max(1),
+                    height: size.height.max(1),
                     depth_or_array_layers: 1,
                 },
                 mip_level_count: 1,
                 sample_count: DEFAULT_MULTI_SAMPLE,
                 dimension: TextureDimension::D2,
-                format: surface_config.format,
+                format,
                 usage: TextureUsages::RENDER_ATTACHMENT,
-                view_formats: &[surface_config.format],
+                view_formats: &[format],
             })
             .create_view(&TextureViewDescriptor::default()),
     )
@@ -1,15 +1,60 @@
 struct Scene {
-    perspective_projection: mat4x4<f32>,
-    light_position: vec4<f32>,
-    light_direction: vec4<f32>,
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
     eye_position: vec4<f32>,
     eye_direction: vec4<f32>,
+    light_count: u32,
+}
+
+// same layout as physics::light::LightSource::get_raw
+struct Light {
+    // w: 0 point, 1 directional, 2 spot
+    position: vec4<f32>,
+    direction: vec4<f32>,
+    intensity: vec4<f32>,
+    // constant, linear, quadratic
+    attenuation: vec4<f32>,
+    // cos of inner and outer angle
+    cone: vec4<f32>,
 }
 
 struct Transform {
-    scale: mat4x4<f32>,
-    rotation: mat4x4<f32>,
-    translation: mat4x4<f32>,
+    model: mat4x4<f32>,
+    // inverse transpose of model
+    normal: mat4x4<f32>,
+}
+
+// same as physics::pattern::MAX_PATTERN_NODES
+const MAX_PATTERN_NODES: u32 = 16u;
+
+// same layout as one node of physics::pattern::Pattern::get_raw
+struct PatternNode {
+    // kind, blend weight, index of sub pattern a and b
+    kind: vec4<f32>,
+    // of solid patterns
+    color: vec4<f32>,
+    // parent pattern space to pattern space
+    inverse: mat4x4<f32>,
+}
+
+// same layout as physics::pattern::Pattern::get_raw, nodes in pre-order
+struct Pattern {
+    // node count
+    count: vec4<f32>,
+    nodes: array<PatternNode, MAX_PATTERN_NODES>,
+}
+
+// same layout as physics::material::Material::get_raw
+struct Material {
+    color: vec4<f32>,
+    ambient: f32,
+    diffuse: f32,
+    specular: f32,
+    shininess: f32,
+    pattern: Pattern,
+    // model, metallic, roughness
+    model: vec4<f32>,
+    emission: vec4<f32>,
 }
 
 struct Input {
@@ -22,55 +67,197 @@ struct Inter {
     @builtin(position) position: vec4<f32>,
     @location(0) color: vec4<f32>,
     @location(1) surface_vector: vec4<f32>,
-    @location(2) surface_light_vector: vec4<f32>,
+    @location(2) surface_position: vec4<f32>,
     @location(3) surface_eye_vector: vec4<f32>,
-    @location(4) light_direction: vec4<f32>,
+    @location(4) object_position: vec4<f32>,
 }
 
 @group(0) @binding(0)
 var<uniform> scene: Scene;
 
+@group(0) @binding(1)
+var<storage, read> lights: array<Light>;
+
 @group(1) @binding(0)
 var<uniform> tran: Transform;
 
+@group(2) @binding(0)
+var<uniform> material: Material;
+
 const PI: f32 = 3.141592653589793238462643;
 
-const SHININESS: f32= 512.0;
+const POINT_LIGHT: u32 = 0u;
+const DIRECTIONAL_LIGHT: u32 = 1u;
+const SPOT_LIGHT: u32 = 2u;
+
+const PBR_MODEL: u32 = 1u;
+// same as physics::pbr
+const MIN_ROUGHNESS: f32 = 0.04;
+const DIELECTRIC_REFLECTANCE: f32 = 0.04;
+
+const SOLID_PATTERN: u32 = 0u;
+const STRIPE_PATTERN: u32 = 1u;
+const GRADIENT_PATTERN: u32 = 2u;
+const RING_PATTERN: u32 = 3u;
+const CHECKER_PATTERN: u32 = 4u;
+const BLEND_PATTERN: u32 = 5u;
 
 @vertex
 fn vs_main(in: Input) -> Inter {
     // object space transformation
-    let transformed = in.position * (tran.scale * tran.rotation * tran.translation);
+    let transformed = in.position * tran.model;
 
     var inter: Inter;
-    inter.position = transformed * scene.perspective_projection;
+    inter.position = transformed * scene.view * scene.projection;
     inter.color = in.color;
-    inter.surface_vector = in.norm * tran.rotation;
-    inter.surface_light_vector = scene.light_position - transformed;
+    inter.surface_vector = in.norm * tran.normal;
+    inter.surface_position = transformed;
     inter.surface_eye_vector = scene.eye_position - transformed;
-    inter.light_direction = scene.light_direction;
+    inter.object_position = in.position;
     return inter;
 }
 
 @fragment
 fn fs_main(inter: Inter) -> @location(0) vec4<f32> {
-    return lighting(inter.color, inter.surface_vector, inter.surface_light_vector, inter.surface_eye_vector, inter.light_direction);
-}
-
-fn lighting(color: vec4<f32>, surface_vector: vec4<f32>, surface_light_vector: vec4<f32>, surface_eye_vector: vec4<f32>, light_direction: vec4<f32>) -> vec4<f32> {
-    let surface_light_norm = normalize(surface_light_vector.xyz);
-    let light_direction_norm = - normalize(light_direction.xyz);
-    let align = dot(surface_light_norm, light_direction_norm);
-    if align > 0.5 {
-        let surface_norm = normalize(surface_vector.xyz);
-        let surface_eye_norm = normalize(surface_eye_vector.xyz);
-        let half_norm = normalize(surface_eye_norm + surface_light_norm);
-        let light = dot(surface_norm, surface_light_norm);
-        var specular = dot(surface_norm, half_norm);
-        specular = select(0.0, pow(specular, SHININESS), specular > 0.0);
-        let color_with_light = vec4<f32>(color.xyz * light + specular, color.w);
-        return color_with_light;
-    } else {
-        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    let surface_norm = normalize(inter.surface_vector.xyz);
+    let surface_eye_norm = normalize(inter.surface_eye_vector.xyz);
+    let is_pbr = u32(material.model.x) == PBR_MODEL;
+    // vertex colors tint Phong only, the PBR base color is the whole albedo
+    // as in physics::pbr
+    var base_color = pattern_color(inter.object_position);
+    if !is_pbr {
+        base_color *= inter.color.xyz;
     }
-}
\ No newline at end of file
+    var color = material.emission.xyz;
+    let light_count = min(scene.light_count, arrayLength(&lights));
+    for (var i = 0u; i < light_count; i++) {
+        if is_pbr {
+            color += pbr_lighting(lights[i], base_color, surface_norm, inter.surface_position.xyz, surface_eye_norm);
+        } else {
+            color += lighting(lights[i], base_color, surface_norm, inter.surface_position.xyz, surface_eye_norm);
+        }
+    }
+    return vec4<f32>(color, inter.color.w);
+}
+
+// same as physics::light::LightModel::direction_from
+fn light_direction(light: Light, surface_position: vec3<f32>) -> vec3<f32> {
+    if u32(light.position.w) == DIRECTIONAL_LIGHT {
+        return -normalize(light.direction.xyz);
+    }
+    return normalize(light.position.xyz - surface_position);
+}
+
+// same as physics::light::LightModel::radiance_at, with attenuation and spot cone
+fn light_radiance(light: Light, surface_position: vec3<f32>) -> vec3<f32> {
+    let kind = u32(light.position.w);
+    if kind == DIRECTIONAL_LIGHT {
+        return light.intensity.xyz;
+    }
+    let surface_light_vector = light.position.xyz - surface_position;
+    let distance = length(surface_light_vector);
+    let attenuation = light.attenuation.x + light.attenuation.y * distance + light.attenuation.z * distance * distance;
+    var intensity = light.intensity.xyz / select(1.0, attenuation, attenuation > 0.0);
+    if kind == SPOT_LIGHT {
+        let align = dot(-surface_light_vector / distance, normalize(light.direction.xyz));
+        intensity = intensity * smoothstep(light.cone.y, light.cone.x, align);
+    }
+    return intensity;
+}
+
+// same as physics::phong::Phong::lighting
+fn lighting(light: Light, color: vec3<f32>, surface_norm: vec3<f32>, surface_position: vec3<f32>, surface_eye_norm: vec3<f32>) -> vec3<f32> {
+    let intensity = light_radiance(light, surface_position);
+    let surface_light_norm = light_direction(light, surface_position);
+
+    let effective_color = color * intensity;
+    let ambient = effective_color * material.ambient;
+    let light_normal = dot(surface_light_norm, surface_norm);
+    if light_normal < 0.0 {
+        // light on the other side of the surface
+        return ambient;
+    }
+    let diffuse = effective_color * material.diffuse * light_normal;
+    let reflect_eye = dot(reflect(-surface_light_norm, surface_norm), surface_eye_norm);
+    let specular = select(vec3<f32>(0.0), intensity * material.specular * pow(reflect_eye, material.shininess), reflect_eye > 0.0);
+    return ambient + diffuse + specular;
+}
+
+// same as physics::pbr::Pbr::reflected_radiance, Cook-Torrance with GGX
+fn pbr_lighting(light: Light, base_color: vec3<f32>, surface_norm: vec3<f32>, surface_position: vec3<f32>, surface_eye_norm: vec3<f32>) -> vec3<f32> {
+    let light_norm = light_direction(light, surface_position);
+    let n_dot_l = dot(surface_norm, light_norm);
+    let half_vector = light_norm + surface_eye_norm;
+    if n_dot_l <= 0.0 || dot(half_vector, half_vector) == 0.0 {
+        return vec3<f32>(0.0);
+    }
+    let half_norm = normalize(half_vector);
+    let n_dot_v = max(dot(surface_norm, surface_eye_norm), 1e-4);
+    let n_dot_h = max(dot(surface_norm, half_norm), 0.0);
+    let v_dot_h = max(dot(surface_eye_norm, half_norm), 0.0);
+    let metallic = material.model.y;
+    let roughness = max(material.model.z, MIN_ROUGHNESS);
+
+    let alpha_2 = pow(roughness, 4.0);
+    let d = n_dot_h * n_dot_h * (alpha_2 - 1.0) + 1.0;
+    let distribution = alpha_2 / (PI * d * d);
+    let k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    let geometry = n_dot_v / (n_dot_v * (1.0 - k) + k) * n_dot_l / (n_dot_l * (1.0 - k) + k);
+    let f_0 = mix(vec3<f32>(DIELECTRIC_REFLECTANCE), base_color, metallic);
+    let fresnel = f_0 + (vec3<f32>(1.0) - f_0) * pow(1.0 - v_dot_h, 5.0);
+
+    let specular = fresnel * (distribution * geometry / max(4.0 * n_dot_v * n_dot_l, 1e-4));
+    let diffuse = (vec3<f32>(1.0) - fresnel) * base_color * ((1.0 - metallic) / PI);
+    return (diffuse + specular) * light_radiance(light, surface_position) * n_dot_l;
+}
+
+// same as physics::pattern::Pattern::color_at for the material pattern,
+// without recursion: points from the root down, colors from the leaves up
+fn pattern_color(object_position: vec4<f32>) -> vec3<f32> {
+    let count = min(u32(material.pattern.count.x), MAX_PATTERN_NODES);
+    var points: array<vec4<f32>, MAX_PATTERN_NODES>;
+    var colors: array<vec3<f32>, MAX_PATTERN_NODES>;
+    points[0] = object_position * material.pattern.nodes[0].inverse;
+    for (var i = 0u; i < count; i++) {
+        let kind = material.pattern.nodes[i].kind;
+        if u32(kind.x) != SOLID_PATTERN {
+            let a = min(u32(kind.z), MAX_PATTERN_NODES - 1u);
+            let b = min(u32(kind.w), MAX_PATTERN_NODES - 1u);
+            points[a] = points[i] * material.pattern.nodes[a].inverse;
+            points[b] = points[i] * material.pattern.nodes[b].inverse;
+        }
+    }
+    for (var i = count; i > 0u; i--) {
+        let node = i - 1u;
+        let kind = material.pattern.nodes[node].kind;
+        let point = points[node];
+        let a = colors[min(u32(kind.z), MAX_PATTERN_NODES - 1u)];
+        let b = colors[min(u32(kind.w), MAX_PATTERN_NODES - 1u)];
+        switch u32(kind.x) {
+            case STRIPE_PATTERN: {
+                colors[node] = alternate(floor(point.x), a, b);
+            }
+            case GRADIENT_PATTERN: {
+                colors[node] = mix(a, b, fract(point.x));
+            }
+            case RING_PATTERN: {
+                colors[node] = alternate(floor(length(point.xz)), a, b);
+            }
+            case CHECKER_PATTERN: {
+                colors[node] = alternate(floor(point.x) + floor(point.y) + floor(point.z), a, b);
+            }
+            case BLEND_PATTERN: {
+                colors[node] = mix(a, b, kind.y);
+            }
+            case SOLID_PATTERN, default: {
+                colors[node] = material.pattern.nodes[node].color.xyz;
+            }
+        }
+    }
+    return colors[0];
+}
+
+// a on even cells
+fn alternate(cell: f32, a: vec3<f32>, b: vec3<f32>) -> vec3<f32> {
+    return select(b, a, cell - 2.0 * floor(cell / 2.0) < 1.0);
+}
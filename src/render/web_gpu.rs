@@ -1,23 +1,41 @@
-use std::sync::Arc;
+use std::sync::{Arc, mpsc};
 
 use wgpu::{
-    BufferAddress, CommandEncoderDescriptor, Device, DeviceDescriptor, Features, FeaturesWGPU,
-    FeaturesWebGPU, Instance, Limits, MemoryHints, PowerPreference, Queue, RequestAdapterOptions,
-    Surface, SurfaceConfiguration, Trace, VertexAttribute, VertexBufferLayout, VertexFormat,
-    VertexStepMode,
+    Adapter, BufferAddress, BufferDescriptor, BufferUsages, COPY_BYTES_PER_ROW_ALIGNMENT,
+    CommandEncoderDescriptor, Device, DeviceDescriptor, Extent3d, Features, FeaturesWGPU,
+    FeaturesWebGPU, Instance, Limits, MapMode, MemoryHints, PollType, PowerPreference, Queue,
+    RequestAdapterOptions, Surface, SurfaceConfiguration, TexelCopyBufferInfo,
+    TexelCopyBufferLayout, Texture, TextureDescriptor, TextureDimension, TextureFormat,
+    TextureUsages, TextureView, TextureViewDescriptor, Trace, VertexAttribute, VertexBufferLayout,
+    VertexFormat, VertexStepMode,
 };
 use winit::{dpi::PhysicalSize, window::Window};
 
 use crate::{
     content::{Vertex, world::World},
     render::render_config::RenderConfig,
+    tracer::canvas::Canvas,
 };
 
+const OFFSCREEN_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+enum RenderTarget<'w> {
+    // present to a window
+    Surface {
+        surface: Surface<'w>,
+        surface_config: SurfaceConfiguration,
+    },
+    // render into an owned texture, pixels can be read back
+    Offscreen {
+        texture: Texture,
+        size: PhysicalSize<u32>,
+    },
+}
+
 pub struct WebGpuContext<'w> {
     pub device: Device,
     pub queue: Queue,
-    surface: Surface<'w>,
-    surface_config: SurfaceConfiguration,
+    target: RenderTarget<'w>,
     pub render_config: RenderConfig,
 }
 
@@ -34,18 +52,7 @@ impl<'w> WebGpuContext<'w> {
             .await
             .expect("fail to find adaptor");
 
-        let (device, queue) = adapter
-            .request_device(&DeviceDescriptor {
-                label: None,
-                required_features: Features {
-                    features_webgpu: FeaturesWebGPU::DEPTH32FLOAT_STENCIL8,
-                    features_wgpu: FeaturesWGPU::empty(),
-                },
-                required_limits: Limits::downlevel_webgl2_defaults()
-                    .using_resolution(adapter.limits()),
-                memory_hints: MemoryHints::Performance,
-                trace: Trace::Off,
-            })
+        let (device, queue) = request_device(&adapter)
             .await
             .expect("fail to create device");
 
@@ -55,11 +62,17 @@ impl<'w> WebGpuContext<'w> {
         let surface_config = surface.get_default_config(&adapter, width, height).unwrap();
         surface.configure(&device, &surface_config);
 
-        let render_config = RenderConfig::new(&device, &surface_config);
+        let render_config = RenderConfig::new(
+            &device,
+            surface_config.format,
+            PhysicalSize::new(width, height),
+        );
 
         Self {
-            surface,
-            surface_config,
+            target: RenderTarget::Surface {
+                surface,
+                surface_config,
+            },
             render_config,
             device,
             queue,
@@ -70,35 +83,226 @@ impl<'w> WebGpuContext<'w> {
         pollster::block_on(WebGpuContext::new_async(window))
     }
 
+    /// render into an offscreen texture instead of a window,
+    /// `force_fallback_adapter` picks the software adapter so no GPU is needed
+    pub async fn new_headless_async(
+        size: PhysicalSize<u32>,
+        force_fallback_adapter: bool,
+    ) -> Result<Self, String> {
+        let instance = Instance::default();
+        let adapter = instance
+            .request_adapter(&RequestAdapterOptions {
+                power_preference: PowerPreference::default(),
+                force_fallback_adapter,
+                compatible_surface: None,
+            })
+            .await
+            .map_err(|err| err.to_string())?;
+
+        let (device, queue) = request_device(&adapter)
+            .await
+            .map_err(|err| err.to_string())?;
+
+        let size = PhysicalSize::new(size.width.max(1), size.height.max(1));
+        let texture = create_offscreen_texture(&device, size);
+        let render_config = RenderConfig::new(&device, OFFSCREEN_FORMAT, size);
+
+        Ok(Self {
+            target: RenderTarget::Offscreen { texture, size },
+            render_config,
+            device,
+            queue,
+        })
+    }
+
+    pub fn new_headless(
+        size: PhysicalSize<u32>,
+        force_fallback_adapter: bool,
+    ) -> Result<Self, String> {
+        pollster::block_on(WebGpuContext::new_headless_async(
+            size,
+            force_fallback_adapter,
+        ))
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        match &self.target {
+            RenderTarget::Surface { surface_config, .. } => {
+                PhysicalSize::new(surface_config.width, surface_config.height)
+            }
+            RenderTarget::Offscreen { size, .. } => *size,
+        }
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        match &self.target {
+            RenderTarget::Surface { surface_config, .. } => surface_config.format,
+            RenderTarget::Offscreen { .. } => OFFSCREEN_FORMAT,
+        }
+    }
+
     pub fn resize(&mut self, size: PhysicalSize<u32>) {
-        self.surface_config.width = size.width.max(1);
-        self.surface_config.height = size.height.max(1);
-        self.surface.configure(&self.device, &self.surface_config);
+        let size = PhysicalSize::new(size.width.max(1), size.height.max(1));
+        match &mut self.target {
+            RenderTarget::Surface {
+                surface,
+                surface_config,
+            } => {
+                surface_config.width = size.width;
+                surface_config.height = size.height;
+                surface.configure(&self.device, surface_config);
+            }
+            RenderTarget::Offscreen {
+                texture,
+                size: target_size,
+            } => {
+                *texture = create_offscreen_texture(&self.device, size);
+                *target_size = size;
+            }
+        }
         self.render_config
-            .update_render_view(&self.device, &self.surface_config);
+            .update_render_view(&self.device, self.format(), size);
     }
+
     pub fn draw(&mut self, world: &World) {
+        match &self.target {
+            RenderTarget::Surface { surface, .. } => {
+                let surface_texture = surface
+                    .get_current_texture()
+                    .expect("Failed to acquire next texture");
+                self.render(
+                    world,
+                    &surface_texture
+                        .texture
+                        .create_view(&TextureViewDescriptor::default()),
+                );
+                surface_texture.present();
+            }
+            RenderTarget::Offscreen { texture, .. } => {
+                self.render(
+                    world,
+                    &texture.create_view(&TextureViewDescriptor::default()),
+                );
+            }
+        }
+    }
+
+    fn render(&self, world: &World, target_view: &TextureView) {
         let mut encoder = self
             .device
             .create_command_encoder(&CommandEncoderDescriptor { label: None });
-
-        let surface_texture = self
-            .surface
-            .get_current_texture()
-            .expect("Failed to acquire next texture");
         //render pass
         {
             let mut render_pass = self
                 .render_config
-                .create_render_pass(&mut encoder, &surface_texture);
+                .create_render_pass(&mut encoder, target_view);
             world.set_pipeline(&mut render_pass)
         }
 
         self.queue.submit(Some(encoder.finish()));
-        surface_texture.present();
+    }
+
+    /// RGBA pixels of the last drawn frame, row by row from the top left,
+    /// only available for offscreen target
+    pub fn read_pixels(&self) -> Result<Vec<u8>, String> {
+        let RenderTarget::Offscreen { texture, size } = &self.target else {
+            return Err("read back only support offscreen target".to_string());
+        };
+
+        let unpadded_bytes_per_row = size.width * 4;
+        let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(COPY_BYTES_PER_ROW_ALIGNMENT)
+            * COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffer = self.device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: (padded_bytes_per_row * size.height) as BufferAddress,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor { label: None });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(size.height),
+                },
+            },
+            Extent3d {
+                width: size.width,
+                height: size.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device
+            .poll(PollType::Wait)
+            .map_err(|err| err.to_string())?;
+        receiver
+            .recv()
+            .map_err(|err| err.to_string())?
+            .map_err(|err| err.to_string())?;
+
+        let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * size.height) as usize);
+        {
+            let data = slice.get_mapped_range();
+            data.chunks(padded_bytes_per_row as usize).for_each(|row| {
+                pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+            });
+        }
+        buffer.unmap();
+        Ok(pixels)
+    }
+
+    /// last drawn frame as canvas, only available for offscreen target
+    pub fn read_canvas(&self) -> Result<Canvas, String> {
+        let size = self.size();
+        Canvas::from_rgba8(size.width, size.height, &self.read_pixels()?)
     }
 }
 
+async fn request_device(adapter: &Adapter) -> Result<(Device, Queue), wgpu::RequestDeviceError> {
+    adapter
+        .request_device(&DeviceDescriptor {
+            label: None,
+            required_features: Features {
+                features_webgpu: FeaturesWebGPU::DEPTH32FLOAT_STENCIL8,
+                features_wgpu: FeaturesWGPU::empty(),
+            },
+            required_limits: Limits::downlevel_defaults().using_resolution(adapter.limits()),
+            memory_hints: MemoryHints::Performance,
+            trace: Trace::Off,
+        })
+        .await
+}
+
+fn create_offscreen_texture(device: &Device, size: PhysicalSize<u32>) -> Texture {
+    device.create_texture(&TextureDescriptor {
+        label: None,
+        size: Extent3d {
+            width: size.width,
+            height: size.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: OFFSCREEN_FORMAT,
+        usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+        view_formats: &[OFFSCREEN_FORMAT],
+    })
+}
+
 pub fn create_vertex_buffer_layout() -> VertexBufferLayout<'static> {
     VertexBufferLayout {
         array_stride: size_of::<Vertex>() as BufferAddress,
@@ -122,3 +326,221 @@ pub fn create_vertex_buffer_layout() -> VertexBufferLayout<'static> {
         ],
     }
 }
+
+// run with `cargo test -- --ignored` on a machine with a GPU or software adapter
+#[test]
+#[ignore = "needs a GPU or software adapter"]
+fn headless_read_back() {
+    use crate::content::{WithGPUBuffer, model_object::generate_ground};
+    use crate::math::{algebra::vector::Vector, geometry::common::Intersection};
+    use crate::physics::{color::Color, common::Illuminated, light::LightModel};
+    use crate::tracer::canvas::to_srgb8;
+
+    let size = PhysicalSize::new(64, 48);
+    let mut context =
+        WebGpuContext::new_headless(size, true).expect("no adapter for headless rendering");
+    let mut world = World::new(size);
+    let ground = generate_ground();
+    let material = ground.get_material().clone();
+    let to_object = ground.world_matrix().inverse().unwrap();
+    world.add_object(ground);
+    world.init_buffer(&context.device, &context.render_config.bind_group_layout);
+    world.update_buffer(&context.queue);
+    context.draw(&world);
+    let pixels = context.read_pixels().unwrap();
+    assert_eq!(pixels.len(), (size.width * size.height * 4) as usize);
+    // cleared to opaque black
+    assert!(pixels.chunks(4).all(|pixel| pixel[3] == 255));
+
+    // rows well below the horizon show the lit ground at y = -1000
+    let lights = world.lights_mut().to_vec();
+    let camera = *world.camera_mut();
+    (36..size.height).step_by(3).for_each(|y| {
+        (0..size.width).step_by(7).for_each(|x| {
+            let ray = camera.ray_for_pixel(x, y, size.width, size.height).unwrap();
+            let t = (-1000.0 - ray.origin.get_value().1) / ray.direction.get_value().1;
+            let point = ray.point_at(t);
+            let hit = Intersection::new(t, ray.direction, point, Vector::unit_y())
+                .with_object_point(to_object * point);
+            let color = lights.iter().fold(Color::black(), |color, light| {
+                color + material.lighting(light as &dyn LightModel, &hit, 1.0)
+            });
+            let (r, g, b) = color.get_value();
+            let index = ((y * size.width + x) * 4) as usize;
+            let gpu = &pixels[index..index + 3];
+            assert!(
+                gpu.iter().any(|value| *value > 0),
+                "pixel ({x}, {y}) not drawn"
+            );
+            [r, g, b].iter().zip(gpu).for_each(|(value, gpu)| {
+                let cpu = to_srgb8(*value);
+                assert!(
+                    cpu.abs_diff(*gpu) <= 2,
+                    "pixel ({x}, {y}): gpu {gpu}, cpu {cpu}"
+                );
+            })
+        })
+    });
+}
+
+// run with `cargo test -- --ignored` on a machine with a GPU or software adapter
+#[test]
+#[ignore = "needs a GPU or software adapter"]
+fn pbr_same_as_cpu() {
+    use crate::content::{WithGPUBuffer, model_object::generate_ground};
+    use crate::math::{
+        algebra::{point::Point, vector::Vector},
+        geometry::common::Intersection,
+    };
+    use crate::physics::{
+        color::Color,
+        common::Illuminated,
+        light::{DirectionalLight, Light, LightModel, PointLight},
+        pbr::Pbr,
+    };
+    use crate::tracer::canvas::to_srgb8;
+
+    let size = PhysicalSize::new(64, 48);
+    let mut context =
+        WebGpuContext::new_headless(size, true).expect("no adapter for headless rendering");
+    let material =
+        Pbr::new(Color::rgb(0.8, 0.5, 0.3), 0.3, 0.4).with_emissive(Color::rgb(0.02, 0.0, 0.0));
+    let lights = [
+        Light::Directional(DirectionalLight::new(
+            Vector::vector(0.3, -1.0, -0.5),
+            Color::white() * 0.8,
+        )),
+        Light::Point(PointLight::new(
+            Point::point(200.0, -600.0, -1500.0),
+            Color::rgb(0.3, 0.3, 0.5),
+        )),
+    ];
+    let mut world = World::new(size);
+    world.lights_mut().copy_from_slice(&lights);
+    // vertex colors do not tint PBR materials
+    let mut ground = generate_ground().with_material(material);
+    ground
+        .mesh
+        .vertices
+        .iter_mut()
+        .for_each(|vertex| vertex.color = Color::rgb(0.2, 0.9, 0.4));
+    world.add_object(ground);
+    world.init_buffer(&context.device, &context.render_config.bind_group_layout);
+    world.update_buffer(&context.queue);
+    context.draw(&world);
+    let pixels = context.read_pixels().unwrap();
+
+    let camera = *world.camera_mut();
+    // rows well below the horizon see the ground at y = -1000
+    (36..size.height).step_by(3).for_each(|y| {
+        (0..size.width).step_by(7).for_each(|x| {
+            let ray = camera.ray_for_pixel(x, y, size.width, size.height).unwrap();
+            let t = (-1000.0 - ray.origin.get_value().1) / ray.direction.get_value().1;
+            let hit = Intersection::new(t, ray.direction, ray.point_at(t), Vector::unit_y());
+            let color = lights
+                .iter()
+                .fold(material.emitted_light(), |color, light| {
+                    color + material.lighting(light as &dyn LightModel, &hit, 1.0)
+                });
+            let (r, g, b) = color.get_value();
+            let index = ((y * size.width + x) * 4) as usize;
+            [r, g, b].iter().enumerate().for_each(|(channel, value)| {
+                let gpu = pixels[index + channel] as i32;
+                // the target is sRGB, shader output is linear
+                let cpu = to_srgb8(*value) as i32;
+                assert!(
+                    (gpu - cpu).abs() <= 2,
+                    "pixel ({x}, {y}) channel {channel}: gpu {gpu}, cpu {cpu}"
+                );
+            })
+        })
+    });
+}
+
+// run with `cargo test -- --ignored` on a machine with a GPU or software adapter
+#[test]
+#[ignore = "needs a GPU or software adapter"]
+fn pattern_same_as_cpu() {
+    use crate::content::{WithGPUBuffer, model_object::generate_ground};
+    use crate::math::{
+        algebra::{matrix::Matrix, vector::Vector},
+        geometry::common::Intersection,
+    };
+    use crate::physics::{
+        color::Color,
+        common::Illuminated,
+        light::{DirectionalLight, Light, LightModel},
+        pattern::Pattern,
+        phong::Phong,
+    };
+    use crate::tracer::canvas::to_srgb8;
+
+    let size = PhysicalSize::new(64, 48);
+    let mut context =
+        WebGpuContext::new_headless(size, true).expect("no adapter for headless rendering");
+    // nested, blended and transformed sub patterns, cells of 300 units
+    // with the ground plane in the middle of a cell
+    let stripe = Pattern::stripe(Color::rgb(1.0, 0.0, 0.0), Color::rgb(0.0, 0.0, 1.0))
+        .with_transform(Matrix::rotate_y(30.0) * Matrix::scale(0.5, 1.0, 1.0))
+        .unwrap();
+    let blend = Pattern::blend(
+        Color::rgb(0.0, 1.0, 0.0),
+        Pattern::gradient(Color::white(), Color::black()),
+        0.5,
+    );
+    let pattern = Pattern::checker(stripe, blend)
+        .with_transform(Matrix::translation(0.0, 150.0, 0.0) * Matrix::scale(300.0, 300.0, 300.0))
+        .unwrap();
+    // ambient only, so the color is the pattern
+    let material = Phong::default()
+        .with_pattern(pattern)
+        .unwrap()
+        .with_ambient(1.0)
+        .with_diffuse(0.0)
+        .with_specular(0.0);
+    let light = Light::Directional(DirectionalLight::new(
+        -Vector::unit_y(),
+        Color::white() * 0.5,
+    ));
+    let mut world = World::new(size);
+    world.lights_mut().fill(light);
+    let lights = world.lights_mut().to_vec();
+    let ground = generate_ground().with_material(material.clone());
+    let to_object = ground.world_matrix().inverse().unwrap();
+    world.add_object(ground);
+    world.init_buffer(&context.device, &context.render_config.bind_group_layout);
+    world.update_buffer(&context.queue);
+    context.draw(&world);
+    let pixels = context.read_pixels().unwrap();
+
+    let camera = *world.camera_mut();
+    let mut colors = vec![];
+    let mut mismatch = 0;
+    // rows well below the horizon see the ground at y = -1000
+    (36..size.height).for_each(|y| {
+        (0..size.width).for_each(|x| {
+            let ray = camera.ray_for_pixel(x, y, size.width, size.height).unwrap();
+            let t = (-1000.0 - ray.origin.get_value().1) / ray.direction.get_value().1;
+            let point = ray.point_at(t);
+            let hit = Intersection::new(t, ray.direction, point, Vector::unit_y())
+                .with_object_point(to_object * point);
+            let color = lights.iter().fold(Color::black(), |color, light| {
+                color + material.lighting(light as &dyn LightModel, &hit, 1.0)
+            });
+            let (r, g, b) = color.get_value();
+            let cpu = [to_srgb8(r), to_srgb8(g), to_srgb8(b)];
+            let index = ((y * size.width + x) * 4) as usize;
+            let gpu = &pixels[index..index + 3];
+            if cpu.iter().zip(gpu).any(|(c, g)| c.abs_diff(*g) > 2) {
+                mismatch += 1;
+            }
+            if !colors.contains(&cpu) {
+                colors.push(cpu);
+            }
+        })
+    });
+    // only pixels on the edges of cells may differ
+    let count = (size.height - 36) * size.width;
+    assert!(mismatch * 20 < count, "{mismatch} of {count} pixels differ");
+    assert!(colors.len() > 3);
+}
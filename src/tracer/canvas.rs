@@ -0,0 +1,344 @@
+use std::{fs, path::Path};
+
+use crate::physics::color::Color;
+
+// max line length of plain PPM
+const PPM_LINE_LIMIT: usize = 70;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PpmFormat {
+    // P3
+    Plain,
+    // P6
+    Raw,
+}
+
+/// CPU image, pixels stored row by row from the top left
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Color::black(); width as usize * height as usize],
+        }
+    }
+
+    /// sRGB encoded rgba with 8 bit per channel, e.g. GPU read back of an
+    /// sRGB target, decoded to linear, alpha is dropped
+    pub fn from_rgba8(width: u32, height: u32, data: &[u8]) -> Result<Self, String> {
+        let size = byte_count(width, height, 4)?;
+        if data.len() != size {
+            return Err(format!(
+                "expect {size} bytes for {width}x{height} rgba, got {}",
+                data.len()
+            ));
+        }
+        let pixels = data
+            .chunks_exact(4)
+            .map(|rgba| {
+                Color::rgb(
+                    from_srgb(to_unit(rgba[0], 255)),
+                    from_srgb(to_unit(rgba[1], 255)),
+                    from_srgb(to_unit(rgba[2], 255)),
+                )
+            })
+            .collect();
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get_pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<Color> {
+        self.index(x, y).map(|index| self.pixels[index])
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: Color) -> Result<(), String> {
+        let index = self
+            .index(x, y)
+            .ok_or(format!("pixel ({x}, {y}) out of canvas"))?;
+        self.pixels[index] = color;
+        Ok(())
+    }
+
+    /// clamp all channels to [0, 1]
+    pub fn clamp(&mut self) {
+        self.pixels
+            .iter_mut()
+            .for_each(|pixel| *pixel = pixel.clamp(0.0, 1.0));
+    }
+
+    /// clamped and sRGB encoded rgb with 8 bit per channel
+    pub fn to_rgb8(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|pixel| {
+                let (r, g, b) = pixel.get_value();
+                [to_srgb8(r), to_srgb8(g), to_srgb8(b)]
+            })
+            .collect()
+    }
+
+    pub fn to_ppm(&self, format: PpmFormat) -> Vec<u8> {
+        match format {
+            PpmFormat::Plain => {
+                let mut ppm = format!("P3\n{} {}\n255\n", self.width, self.height);
+                self.to_rgb8()
+                    .chunks(self.width.max(1) as usize * 3)
+                    .for_each(|row| {
+                        // break rows into lines not longer than the limit
+                        let mut line = String::new();
+                        row.iter().for_each(|value| {
+                            let value = value.to_string();
+                            if !line.is_empty() && line.len() + 1 + value.len() > PPM_LINE_LIMIT {
+                                ppm.push_str(&line);
+                                ppm.push('\n');
+                                line.clear();
+                            }
+                            if !line.is_empty() {
+                                line.push(' ');
+                            }
+                            line.push_str(&value);
+                        });
+                        ppm.push_str(&line);
+                        ppm.push('\n');
+                    });
+                ppm.into_bytes()
+            }
+            PpmFormat::Raw => {
+                let mut ppm = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+                ppm.extend(self.to_rgb8());
+                ppm
+            }
+        }
+    }
+
+    /// read plain (P3) or raw (P6) PPM, values are decoded from sRGB
+    pub fn from_ppm(data: &[u8]) -> Result<Self, String> {
+        let mut reader = PpmReader { data, position: 0 };
+        let magic = reader.token()?;
+        let width = reader.number()?;
+        let height = reader.number()?;
+        let max_value = reader.number()?;
+        if max_value == 0 || max_value > 65535 {
+            return Err(format!("invalid max value {max_value}"));
+        }
+        let count = byte_count(width, height, 3)?;
+        let values: Vec<u32> = match magic.as_str() {
+            "P3" => (0..count)
+                .map(|_| reader.number())
+                .collect::<Result<_, _>>()?,
+            "P6" => {
+                // single whitespace before the raster
+                let start = reader.position + 1;
+                let size = if max_value < 256 { 1 } else { 2 };
+                let end = count
+                    .checked_mul(size)
+                    .and_then(|length| length.checked_add(start))
+                    .ok_or(format!("PPM size {width}x{height} too large"))?;
+                let raster = data
+                    .get(start..end)
+                    .ok_or("PPM raster too short".to_string())?;
+                raster
+                    .chunks_exact(size)
+                    .map(|bytes| {
+                        bytes
+                            .iter()
+                            .fold(0, |value, byte| value * 256 + *byte as u32)
+                    })
+                    .collect()
+            }
+            _ => return Err(format!("unsupported PPM format {magic}")),
+        };
+        if let Some(value) = values.iter().find(|value| **value > max_value) {
+            return Err(format!("value {value} over max value {max_value}"));
+        }
+        let pixels = values
+            .chunks_exact(3)
+            .map(|rgb| {
+                Color::rgb(
+                    from_srgb(to_unit(rgb[0], max_value)),
+                    from_srgb(to_unit(rgb[1], max_value)),
+                    from_srgb(to_unit(rgb[2], max_value)),
+                )
+            })
+            .collect();
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>, String> {
+        let mut png = vec![];
+        let mut encoder = png::Encoder::new(&mut png, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|err| err.to_string())?;
+        writer
+            .write_image_data(&self.to_rgb8())
+            .map_err(|err| err.to_string())?;
+        writer.finish().map_err(|err| err.to_string())?;
+        Ok(png)
+    }
+
+    pub fn save_ppm(&self, path: impl AsRef<Path>, format: PpmFormat) -> Result<(), String> {
+        fs::write(path, self.to_ppm(format)).map_err(|err| err.to_string())
+    }
+
+    pub fn load_ppm(path: impl AsRef<Path>) -> Result<Self, String> {
+        Self::from_ppm(&fs::read(path).map_err(|err| err.to_string())?)
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        fs::write(path, self.to_png()?).map_err(|err| err.to_string())
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        (x < self.width && y < self.height).then_some(y as usize * self.width as usize + x as usize)
+    }
+}
+
+/// clamped linear value to 8 bit sRGB
+pub fn to_srgb8(linear: f32) -> u8 {
+    (to_srgb(linear.clamp(0.0, 1.0)) * 255.0).round() as u8
+}
+
+/// sRGB transfer function, linear value in [0, 1]
+pub fn to_srgb(linear: f32) -> f32 {
+    if linear <= 0.0031308 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// inverse of `to_srgb`
+pub fn from_srgb(srgb: f32) -> f32 {
+    if srgb <= 0.04045 {
+        srgb / 12.92
+    } else {
+        ((srgb + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+// bytes for the image, Err if not addressable
+fn byte_count(width: u32, height: u32, channels: usize) -> Result<usize, String> {
+    (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|count| count.checked_mul(channels))
+        .ok_or(format!("image size {width}x{height} too large"))
+}
+
+fn to_unit(value: impl Into<u32>, max_value: u32) -> f32 {
+    value.into() as f32 / max_value as f32
+}
+
+// header tokens of PPM, skipping whitespace and comments
+struct PpmReader<'d> {
+    data: &'d [u8],
+    position: usize,
+}
+
+impl PpmReader<'_> {
+    fn token(&mut self) -> Result<String, String> {
+        loop {
+            match self.data.get(self.position) {
+                Some(b'#') => {
+                    while self
+                        .data
+                        .get(self.position)
+                        .is_some_and(|byte| *byte != b'\n')
+                    {
+                        self.position += 1;
+                    }
+                }
+                Some(byte) if byte.is_ascii_whitespace() => self.position += 1,
+                Some(_) => break,
+                None => return Err("unexpected end of PPM".to_string()),
+            }
+        }
+        let start = self.position;
+        while self
+            .data
+            .get(self.position)
+            .is_some_and(|byte| !byte.is_ascii_whitespace())
+        {
+            self.position += 1;
+        }
+        Ok(String::from_utf8_lossy(&self.data[start..self.position]).to_string())
+    }
+
+    fn number(&mut self) -> Result<u32, String> {
+        let token = self.token()?;
+        token
+            .parse()
+            .map_err(|_| format!("invalid number {token} in PPM"))
+    }
+}
+
+#[test]
+fn ppm_round_trip() {
+    use crate::math::algebra::common::FuzzyEq;
+
+    let mut canvas = Canvas::new(30, 2);
+    canvas.set_pixel(0, 0, Color::rgb(1.5, 0.0, 0.0)).unwrap();
+    canvas.set_pixel(2, 1, Color::rgb(0.0, 0.5, 1.0)).unwrap();
+    assert!(canvas.set_pixel(30, 0, Color::white()).is_err());
+
+    let plain = canvas.to_ppm(PpmFormat::Plain);
+    let text = String::from_utf8(plain.clone()).unwrap();
+    assert!(text.starts_with("P3\n30 2\n255\n255 0 0 0"));
+    assert!(text.lines().all(|line| line.len() <= PPM_LINE_LIMIT));
+
+    canvas.clamp();
+    [PpmFormat::Plain, PpmFormat::Raw]
+        .into_iter()
+        .for_each(|format| {
+            let read = Canvas::from_ppm(&canvas.to_ppm(format)).unwrap();
+            assert!(
+                read.get_pixel(0, 0)
+                    .unwrap()
+                    .fuzzy_eq(&Color::rgb(1.0, 0.0, 0.0))
+            );
+            assert_eq!(read.to_rgb8(), canvas.to_rgb8());
+        });
+
+    let png = canvas.to_png().unwrap();
+    assert_eq!(&png[1..4], b"PNG");
+}
+
+#[test]
+fn srgb_and_size() {
+    // linear half is brighter in sRGB
+    let canvas = Canvas::from_rgba8(1, 1, &[188, 0, 255, 255]).unwrap();
+    let (r, g, b) = canvas.get_pixel(0, 0).unwrap().get_value();
+    assert!((r - 0.5).abs() < 0.01 && g == 0.0 && (b - 1.0).abs() < 1e-6);
+    assert_eq!(canvas.to_rgb8(), vec![188, 0, 255]);
+    assert!((0..=255).all(|byte| to_srgb8(from_srgb(to_unit(byte as u32, 255))) == byte));
+
+    assert!(Canvas::from_rgba8(2, 1, &[0; 4]).is_err());
+    assert!(Canvas::from_ppm(b"P6\n4294967295 4294967295\n255\n").is_err());
+    assert!(Canvas::from_ppm(b"P3\n65536 65536\n255\n0 0 0").is_err());
+}
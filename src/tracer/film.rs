@@ -0,0 +1,90 @@
+use crate::physics::color::Color;
+
+use super::canvas::Canvas;
+
+/// sum of samples for each pixel, rows from the top left,
+/// the average can be previewed at any time while more passes arrive
+#[derive(Debug, Clone)]
+pub struct Film {
+    width: u32,
+    height: u32,
+    sum: Vec<Color>,
+    sample_count: u32,
+}
+
+impl Film {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            sum: vec![Color::black(); width as usize * height as usize],
+            sample_count: 0,
+        }
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    /// samples per pixel so far
+    pub fn get_sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// one sample for every pixel
+    pub fn add_pass(&mut self, pass: &[Color]) -> Result<(), String> {
+        if pass.len() != self.sum.len() {
+            return Err(format!(
+                "expect {} samples for {}x{}, got {}",
+                self.sum.len(),
+                self.width,
+                self.height,
+                pass.len()
+            ));
+        }
+        self.sum
+            .iter_mut()
+            .zip(pass)
+            .for_each(|(sum, sample)| *sum = *sum + *sample);
+        self.sample_count += 1;
+        Ok(())
+    }
+
+    /// average of the samples, black before the first pass
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<Color> {
+        (x < self.width && y < self.height).then(|| self.average(&self.sum[self.index(x, y)]))
+    }
+
+    pub fn to_canvas(&self) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height);
+        (0..self.height).for_each(|y| {
+            (0..self.width).for_each(|x| {
+                let color = self.average(&self.sum[self.index(x, y)]);
+                canvas.set_pixel(x, y, color).unwrap();
+            })
+        });
+        canvas
+    }
+
+    pub fn clear(&mut self) {
+        self.sum.fill(Color::black());
+        self.sample_count = 0;
+    }
+
+    fn average(&self, sum: &Color) -> Color {
+        if self.sample_count == 0 {
+            Color::black()
+        } else {
+            *sum * (1.0 / self.sample_count as f32)
+        }
+    }
+
+    // x and y inside the film
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+}
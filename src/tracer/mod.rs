@@ -0,0 +1,7 @@
+pub mod canvas;
+pub mod film;
+pub mod path;
+pub mod sampling;
+pub mod scene;
+pub mod tile;
+pub mod whitted;
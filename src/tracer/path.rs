@@ -0,0 +1,369 @@
+use rand::Rng;
+
+use crate::{
+    constant::{PATH_DEPTH_LIMIT, ROULETTE_DEPTH},
+    content::scene::Camera,
+    math::geometry::{common::Intersection, ray::Ray},
+    physics::{
+        color::Color,
+        common::{Illuminated, schlick},
+        material::Material,
+        pbr::Pbr,
+    },
+};
+
+use super::{
+    canvas::Canvas,
+    film::Film,
+    sampling::{cosine_hemisphere, ggx_half_vector, stream_rng},
+    scene::TraceScene,
+    tile::{CancelToken, RenderProgress, TileRenderer},
+};
+
+/// Monte Carlo path tracer with diffuse interreflection,
+/// lit by emissive objects and the background
+pub struct PathTracer<'s> {
+    scene: &'s TraceScene,
+    seed: u64,
+    depth_limit: usize,
+    roulette_depth: usize,
+}
+
+impl<'s> PathTracer<'s> {
+    pub fn new(scene: &'s TraceScene) -> Self {
+        Self {
+            scene,
+            seed: 0,
+            depth_limit: PATH_DEPTH_LIMIT,
+            roulette_depth: ROULETTE_DEPTH,
+        }
+    }
+
+    /// same seed, same image
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_depth_limit(mut self, depth_limit: usize) -> Self {
+        self.depth_limit = depth_limit;
+        self
+    }
+
+    pub fn with_roulette_depth(mut self, roulette_depth: usize) -> Self {
+        self.roulette_depth = roulette_depth;
+        self
+    }
+
+    pub fn render(&self, camera: &Camera, width: u32, height: u32, samples: u32) -> Canvas {
+        let mut film = Film::new(width, height);
+        (0..samples).for_each(|_| self.render_pass(camera, &mut film));
+        film.to_canvas()
+    }
+
+    /// one more sample for every pixel, passes are numbered by the samples
+    /// already in the film
+    pub fn render_pass(&self, camera: &Camera, film: &mut Film) {
+        let (width, height) = (film.get_width(), film.get_height());
+        let pass = film.get_sample_count();
+        let samples: Vec<Color> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| self.sample_pixel(camera, x, y, width, height, pass))
+            .collect();
+        film.add_pass(&samples).unwrap();
+    }
+
+    /// same pass as `render_pass` on worker threads, the film is unchanged
+    /// if cancelled
+    pub fn render_pass_tiled(
+        &self,
+        camera: &Camera,
+        film: &mut Film,
+        renderer: &TileRenderer,
+        progress: impl FnMut(RenderProgress),
+        cancel: &CancelToken,
+    ) -> Result<(), String> {
+        let (width, height) = (film.get_width(), film.get_height());
+        let pass = film.get_sample_count();
+        let canvas = renderer.render(
+            width,
+            height,
+            |x, y| self.sample_pixel(camera, x, y, width, height, pass),
+            progress,
+            cancel,
+        )?;
+        film.add_pass(canvas.get_pixels())
+    }
+
+    /// radiance through a random position in pixel (x, y),
+    /// only depends on the seed, the pass and the pixel
+    pub fn sample_pixel(
+        &self,
+        camera: &Camera,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        pass: u32,
+    ) -> Color {
+        let stream = ((pass as u64) << 32) | (y as u64 * width as u64 + x as u64);
+        let mut rng = stream_rng(self.seed, stream);
+        let (jitter_x, jitter_y): (f32, f32) = (rng.r#gen(), rng.r#gen());
+        camera
+            .ray_through(x as f32 + jitter_x, y as f32 + jitter_y, width, height)
+            .map(|ray| self.radiance(&ray, &mut rng))
+            .unwrap_or(self.scene.background)
+    }
+
+    /// one random path, the expectation is the light arriving along the ray
+    pub fn radiance(&self, ray: &Ray, rng: &mut impl Rng) -> Color {
+        let mut radiance = Color::black();
+        // share of the light on the path that reaches the eye
+        let mut throughput = Color::white();
+        let mut ray = *ray;
+        for depth in 0..self.depth_limit {
+            let Some(hit) = self.scene.nearest_hit(&ray) else {
+                return radiance + throughput * self.scene.background;
+            };
+            let Some(object) = hit
+                .get_target()
+                .and_then(|target| self.scene.get_object(target))
+            else {
+                break;
+            };
+            radiance = radiance + throughput * object.material.emitted_light();
+            let Some((next, weight)) = self.scatter(&hit, &object.material, rng) else {
+                break;
+            };
+            throughput = throughput * weight;
+
+            // end dim paths early, survivors carry the light of the ended ones
+            if depth + 1 >= self.roulette_depth {
+                let (r, g, b) = throughput.get_value();
+                let survival = r.max(g).max(b).min(0.95);
+                if survival <= 0.0 || rng.r#gen::<f32>() >= survival {
+                    break;
+                }
+                throughput = throughput * (1.0 / survival);
+            }
+            ray = next;
+        }
+        radiance
+    }
+
+    // next ray of the path and the share of its light leaving towards the eye,
+    // for Phong one of mirror reflection, refraction or diffuse is picked by their weight
+    fn scatter(
+        &self,
+        hit: &Intersection,
+        material: &Material,
+        rng: &mut impl Rng,
+    ) -> Option<(Ray, Color)> {
+        let phong = match material {
+            Material::Phong(phong) => phong,
+            Material::Pbr(pbr) => return scatter_pbr(hit, pbr, rng),
+        };
+        let normal = hit.get_shading_normal();
+        let eye_v = hit.get_eye_v();
+        let reflectiveness = phong.get_reflectiveness();
+        let transparency = phong.get_transparency();
+        let albedo = phong.color_at(&hit.get_object_point()) * phong.get_diffuse();
+        let reflect_chance = reflectiveness.clamp(0.0, 1.0);
+        let refract_chance = transparency.clamp(0.0, 1.0 - reflect_chance);
+        let mirror = |tint: Color| {
+            let direction = eye_v.reflect(&normal).ok()?;
+            Ray::new(hit.get_over_point(), direction)
+                .ok()
+                .map(|ray| (ray, tint))
+        };
+
+        let choice: f32 = rng.r#gen();
+        if choice < reflect_chance {
+            return mirror(Color::white() * (reflectiveness / reflect_chance));
+        }
+        if choice < reflect_chance + refract_chance {
+            // a single medium, nested objects are not tracked
+            let refractive_index = material.refractive_index();
+            let (n_1, n_2) = if hit.is_front_face() {
+                (1.0, refractive_index)
+            } else {
+                (refractive_index, 1.0)
+            };
+            let reflectance = schlick(eye_v.dot(&normal), n_1, n_2);
+            if rng.r#gen::<f32>() < reflectance {
+                return mirror(Color::white() * (transparency / refract_chance));
+            }
+            return eye_v
+                .refract(&normal, n_1 / n_2)
+                .and_then(|direction| Ray::new(hit.get_under_point(), direction).ok())
+                .map(|ray| (ray, Color::white() * (transparency / refract_chance)));
+        }
+
+        // cosine sampling cancels the cos / PI of a lambertian surface
+        let diffuse_chance = 1.0 - reflect_chance - refract_chance;
+        let albedo = albedo * (1.0 / diffuse_chance);
+        let direction = cosine_hemisphere(&normal, rng.r#gen(), rng.r#gen());
+        Ray::new(hit.get_over_point(), direction)
+            .ok()
+            .map(|ray| (ray, albedo))
+    }
+}
+
+// GGX specular or lambertian diffuse, each sampled by its own density and
+// weighted by the same BRDF as `Pbr::reflected_radiance`
+fn scatter_pbr(hit: &Intersection, pbr: &Pbr, rng: &mut impl Rng) -> Option<(Ray, Color)> {
+    let normal = hit.get_shading_normal();
+    let eye_v = hit.get_eye_v();
+    // metals have no diffuse part
+    let specular_chance = 0.5 + 0.5 * pbr.get_metallic();
+    let (light_v, weight) = if rng.r#gen::<f32>() < specular_chance {
+        let half_v = ggx_half_vector(&normal, pbr.get_alpha(), rng.r#gen(), rng.r#gen());
+        let light_v = eye_v.reflect(&half_v).ok()?;
+        let weight = pbr.specular_weight(&normal, &eye_v, &light_v) * (1.0 / specular_chance);
+        (light_v, weight)
+    } else {
+        let light_v = cosine_hemisphere(&normal, rng.r#gen(), rng.r#gen());
+        let weight =
+            pbr.diffuse_weight(&normal, &eye_v, &light_v) * (1.0 / (1.0 - specular_chance));
+        (light_v, weight)
+    };
+    // below the surface, the light is absorbed
+    if normal.dot(&light_v) <= 0.0 {
+        return None;
+    }
+    Ray::new(hit.get_over_point(), light_v)
+        .ok()
+        .map(|ray| (ray, weight))
+}
+
+#[test]
+fn furnace_and_seed() {
+    use crate::math::{
+        algebra::{point::Point, vector::Vector},
+        geometry::{plane::Plane, sphere::Sphere},
+    };
+    use crate::physics::phong::Phong;
+
+    let camera = Camera::new(
+        Point::point(0.0, 0.0, -5.0),
+        Point::origin(),
+        Vector::unit_y(),
+        40.0,
+        1.0,
+        0.1,
+        100.0,
+    );
+
+    // a convex diffuse object under a white sky reflects exactly its albedo
+    let mut scene = TraceScene::new().with_background(Color::white());
+    scene.add_object(
+        Box::new(Sphere::new(Point::origin(), 1.0)),
+        Phong::default().with_diffuse(0.5),
+    );
+    let canvas = PathTracer::new(&scene).render(&camera, 8, 8, 4);
+    assert!((canvas.get_pixel(4, 4).unwrap().get_r() - 0.5).abs() < 1e-4);
+    assert!((canvas.get_pixel(0, 0).unwrap().get_r() - 1.0).abs() < 1e-4);
+
+    // lit by an emissive sphere only
+    let mut scene = TraceScene::new();
+    scene.add_object(
+        Box::new(Plane::new(Point::point(0.0, -1.0, 0.0), Vector::unit_y(), 100.0).unwrap()),
+        Phong::default(),
+    );
+    scene.add_object(
+        Box::new(Sphere::new(Point::point(0.0, 1.0, 0.0), 0.5)),
+        Phong::default()
+            .with_diffuse(0.0)
+            .with_emission(Color::rgb(4.0, 4.0, 4.0)),
+    );
+    let tracer = PathTracer::new(&scene).with_seed(1);
+    let mut film = Film::new(12, 12);
+    tracer.render_pass(&camera, &mut film);
+    tracer.render_pass(&camera, &mut film);
+    let canvas = tracer.render(&camera, 12, 12, 2);
+    assert_eq!(film.get_sample_count(), 2);
+    // the same on worker threads
+    let mut tiled = Film::new(12, 12);
+    let renderer = TileRenderer::new().with_tile_size(5).with_thread_count(3);
+    (0..2).for_each(|_| {
+        tracer
+            .render_pass_tiled(&camera, &mut tiled, &renderer, |_| {}, &CancelToken::new())
+            .unwrap()
+    });
+    assert!(
+        tiled
+            .to_canvas()
+            .get_pixels()
+            .iter()
+            .zip(film.to_canvas().get_pixels())
+            .all(|(a, b)| a.get_value() == b.get_value())
+    );
+    assert!(
+        canvas
+            .get_pixels()
+            .iter()
+            .zip(film.to_canvas().get_pixels())
+            .all(|(a, b)| a.get_value() == b.get_value())
+    );
+    // the plane gets some light
+    assert!((8..12).any(|y| (0..12).any(|x| canvas.get_pixel(x, y).unwrap().get_r() > 0.0)));
+
+    let other = PathTracer::new(&scene)
+        .with_seed(2)
+        .render(&camera, 12, 12, 2);
+    assert!(
+        canvas
+            .get_pixels()
+            .iter()
+            .zip(other.get_pixels())
+            .any(|(a, b)| a.get_value() != b.get_value())
+    );
+}
+
+#[test]
+fn pbr_scatter_matches_brdf() {
+    use std::f32::consts::PI;
+
+    use crate::math::algebra::{point::Point, vector::Vector};
+    use crate::tracer::sampling::orthonormal_basis;
+
+    let normal = Vector::unit_y();
+    let direction = Vector::vector(0.5, -1.0, 0.2).unit().unwrap();
+    let hit = Intersection::new(1.0, direction, Point::origin(), normal);
+    let eye_v = hit.get_eye_v();
+    let count = 100000;
+    [(0.0, 0.6), (0.5, 0.4), (1.0, 0.3)]
+        .into_iter()
+        .for_each(|(metallic, roughness)| {
+            let pbr = Pbr::new(Color::rgb(0.9, 0.6, 0.3), metallic, roughness);
+            // importance sampled, as the path tracer does
+            let mut rng = stream_rng(3, 0);
+            let sampled = (0..count)
+                .filter_map(|_| scatter_pbr(&hit, &pbr, &mut rng))
+                .fold(Color::black(), |sum, (_, weight)| sum + weight)
+                * (1.0 / count as f32);
+            // reflected white light from uniform directions on the hemisphere
+            let (tangent, bitangent) = orthonormal_basis(&normal);
+            let integral = (0..count)
+                .map(|_| {
+                    let cos: f32 = rng.r#gen();
+                    let phi = 2.0 * PI * rng.r#gen::<f32>();
+                    let sin = (1.0 - cos * cos).sqrt();
+                    let light_v =
+                        tangent * (sin * phi.cos()) + bitangent * (sin * phi.sin()) + normal * cos;
+                    pbr.reflected_radiance(&normal, &eye_v, &light_v, Color::white())
+                })
+                .fold(Color::black(), |sum, color| sum + color)
+                * (2.0 * PI / count as f32);
+            let (r, g, b) = sampled.get_value();
+            let (ir, ig, ib) = integral.get_value();
+            [(r, ir), (g, ig), (b, ib)]
+                .iter()
+                .for_each(|(value, expect)| {
+                    assert!(
+                        (value - expect).abs() < 0.03 * expect.max(0.1),
+                        "metallic {metallic}: sampled {value}, integral {expect}"
+                    )
+                });
+        });
+}
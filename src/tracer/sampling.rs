@@ -0,0 +1,98 @@
+use std::f32::consts::PI;
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::math::algebra::{point::Point, vector::Vector};
+
+/// random generator for one stream of a seeded render, e.g. one pixel of one pass,
+/// so the result does not depend on the order streams are rendered in
+pub fn stream_rng(seed: u64, stream: u64) -> StdRng {
+    StdRng::seed_from_u64(mix(seed ^ mix(stream)))
+}
+
+/// random generator for a point in space, e.g. a shaded point, when there
+/// is no pixel to seed from
+pub fn point_rng(seed: u64, point: &Point) -> StdRng {
+    let (x, y, z) = point.get_value();
+    stream_rng(
+        seed,
+        mix(mix(x.to_bits() as u64) ^ y.to_bits() as u64) ^ z.to_bits() as u64,
+    )
+}
+
+/// one jittered point in each cell of a grid over the unit square, the count
+/// is rounded up to fill the grid
+pub fn stratified(count: u32, rng: &mut impl Rng) -> Vec<(f32, f32)> {
+    let columns = (count.max(1) as f32).sqrt().ceil() as u32;
+    let rows = count.max(1).div_ceil(columns);
+    (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (column, row)))
+        .map(|(column, row)| {
+            (
+                (column as f32 + rng.r#gen::<f32>()) / columns as f32,
+                (row as f32 + rng.r#gen::<f32>()) / rows as f32,
+            )
+        })
+        .collect()
+}
+
+// splitmix64 finalizer
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// two unit vectors perpendicular to the unit normal and each other
+pub fn orthonormal_basis(normal: &Vector) -> (Vector, Vector) {
+    let (x, y, z) = normal.get_value();
+    let sign = 1.0_f32.copysign(z);
+    let a = -1.0 / (sign + z);
+    let b = x * y * a;
+    (
+        Vector::vector(1.0 + sign * x * x * a, sign * b, -sign * x),
+        Vector::vector(b, sign + y * y * a, -y),
+    )
+}
+
+/// direction on the hemisphere around the unit normal with density cos / PI,
+/// u_1 and u_2 uniform in [0, 1)
+pub fn cosine_hemisphere(normal: &Vector, u_1: f32, u_2: f32) -> Vector {
+    let (tangent, bitangent) = orthonormal_basis(normal);
+    let phi = 2.0 * PI * u_1;
+    let radius = u_2.sqrt();
+    tangent * (radius * phi.cos())
+        + bitangent * (radius * phi.sin())
+        + *normal * (1.0 - u_2).max(0.0).sqrt()
+}
+
+/// half vector around the unit normal with density D * cos of the GGX
+/// distribution with the given alpha, u_1 and u_2 uniform in [0, 1)
+pub fn ggx_half_vector(normal: &Vector, alpha: f32, u_1: f32, u_2: f32) -> Vector {
+    let (tangent, bitangent) = orthonormal_basis(normal);
+    let phi = 2.0 * PI * u_1;
+    let cos_2 = ((1.0 - u_2) / (1.0 + (alpha * alpha - 1.0) * u_2)).clamp(0.0, 1.0);
+    let sin = (1.0 - cos_2).sqrt();
+    tangent * (sin * phi.cos()) + bitangent * (sin * phi.sin()) + *normal * cos_2.sqrt()
+}
+
+#[test]
+fn test_cosine_hemisphere() {
+    let normal = Vector::vector(1.0, 2.0, -3.0).unit().unwrap();
+    let (tangent, bitangent) = orthonormal_basis(&normal);
+    assert!(tangent.dot(&normal).abs() < 1e-5 && bitangent.dot(&tangent).abs() < 1e-5);
+
+    let mut rng = stream_rng(7, 0);
+    let count = 20000;
+    let mean_cos = (0..count)
+        .map(|_| {
+            let direction = cosine_hemisphere(&normal, rng.r#gen(), rng.r#gen());
+            assert!((direction.norm() - 1.0).abs() < 1e-4);
+            direction.dot(&normal)
+        })
+        .sum::<f32>()
+        / count as f32;
+    // expectation of cos with density cos / PI
+    assert!((mean_cos - 2.0 / 3.0).abs() < 0.01);
+}
@@ -0,0 +1,201 @@
+use std::sync::OnceLock;
+
+use rand::Rng;
+
+use crate::{
+    math::{
+        algebra::{point::Point, vector::Vector},
+        geometry::{
+            bounds::Aabb,
+            bvh::Bvh,
+            common::{Intersect, IntersectTargetID, Intersection, Intersections},
+            ray::Ray,
+        },
+    },
+    physics::{color::Color, common::Illuminated, light::LightModel, material::Material},
+};
+
+use super::sampling::stratified;
+
+pub struct TraceObject {
+    pub shape: Box<dyn Intersect>,
+    pub material: Material,
+}
+
+/// shapes and lights for the CPU tracer
+pub struct TraceScene {
+    objects: Vec<TraceObject>,
+    lights: Vec<Box<dyn LightModel>>,
+    pub background: Color,
+    bvh: OnceLock<Bvh>,
+}
+
+impl TraceScene {
+    pub fn new() -> Self {
+        Self {
+            objects: vec![],
+            lights: vec![],
+            background: Color::black(),
+            bvh: OnceLock::new(),
+        }
+    }
+
+    pub fn with_background(mut self, background: Color) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// index of the object in the scene
+    pub fn add_object(
+        &mut self,
+        shape: Box<dyn Intersect>,
+        material: impl Into<Material>,
+    ) -> usize {
+        self.objects.push(TraceObject {
+            shape,
+            material: material.into(),
+        });
+        self.bvh = OnceLock::new();
+        self.objects.len() - 1
+    }
+
+    pub fn add_light(&mut self, light: impl LightModel + 'static) {
+        self.lights.push(Box::new(light));
+    }
+
+    pub fn get_objects(&self) -> &[TraceObject] {
+        &self.objects
+    }
+
+    pub fn get_lights(&self) -> &[Box<dyn LightModel>] {
+        &self.lights
+    }
+
+    pub fn get_object(&self, target: IntersectTargetID) -> Option<&TraceObject> {
+        self.objects.get(target as usize)
+    }
+
+    /// all intersections sorted by t, targeted at the index of the object
+    pub fn intersections(&self, ray: &Ray) -> Intersections {
+        let intersections = self
+            .bvh()
+            .all(ray, |index| self.intersect_object(index, ray));
+        Intersections::new(intersections.into_iter().map(|(_, inter)| inter).collect())
+    }
+
+    /// nearest intersection in front of the ray, targeted at the index of the object
+    pub fn nearest_hit(&self, ray: &Ray) -> Option<Intersection> {
+        self.bvh()
+            .nearest(ray, f32::MIN_POSITIVE, f32::INFINITY, |index| {
+                self.intersect_object(index, ray)
+            })
+            .map(|(_, inter)| inter)
+    }
+
+    /// fraction of light from the light reaching the point, averaged over
+    /// stratified positions on lights with an area
+    pub fn visibility(&self, point: &Point, light: &dyn LightModel, rng: &mut impl Rng) -> f32 {
+        if light.sample_count() <= 1 {
+            return self.transmittance(point, &light.sample_position(point, 0.5, 0.5));
+        }
+        let samples = stratified(light.sample_count(), rng);
+        let visible: f32 = samples
+            .iter()
+            .map(|(u, v)| self.transmittance(point, &light.sample_position(point, *u, *v)))
+            .sum();
+        visible / samples.len() as f32
+    }
+
+    /// true if any object is between the two points
+    pub fn is_blocked(&self, from: &Point, to: &Point) -> bool {
+        self.is_blocked_by(from, to, |_| true)
+    }
+
+    /// fraction of light passing between the two points, 0.0 behind opaque
+    /// objects, transparent objects dim the light once per object
+    pub fn transmittance(&self, from: &Point, to: &Point) -> f32 {
+        if self.is_blocked_by(from, to, |object| !object.material.transparent()) {
+            return 0.0;
+        }
+        let to_target = Vector::from_points(from, to);
+        let distance = to_target.norm();
+        let Ok(ray) = Ray::new(*from, to_target) else {
+            return 1.0;
+        };
+        let mut passed: Vec<usize> = vec![];
+        self.bvh()
+            .all(&ray, |index| {
+                if self.objects[index].material.transparent() {
+                    self.objects[index].shape.intersect(&ray)
+                } else {
+                    vec![]
+                }
+            })
+            .into_iter()
+            .filter(|(_, inter)| inter.get_t() >= f32::MIN_POSITIVE && inter.get_t() < distance)
+            .for_each(|(index, _)| {
+                if !passed.contains(&index) {
+                    passed.push(index);
+                }
+            });
+        passed
+            .iter()
+            .map(|index| {
+                // share of white light passing the surface
+                let (r, g, b) = self.objects[*index]
+                    .material
+                    .refract_light(&Color::white())
+                    .get_value();
+                (r + g + b) / 3.0
+            })
+            .product()
+    }
+
+    // true if any object accepted by the filter is between the two points
+    fn is_blocked_by(
+        &self,
+        from: &Point,
+        to: &Point,
+        filter: impl Fn(&TraceObject) -> bool,
+    ) -> bool {
+        let to_target = Vector::from_points(from, to);
+        let distance = to_target.norm();
+        match Ray::new(*from, to_target) {
+            Ok(ray) => self.bvh().any(&ray, f32::MIN_POSITIVE, distance, |index| {
+                if filter(&self.objects[index]) {
+                    self.objects[index].shape.intersect(&ray)
+                } else {
+                    vec![]
+                }
+            }),
+            Err(_) => false,
+        }
+    }
+
+    fn intersect_object(&self, index: usize, ray: &Ray) -> Vec<Intersection> {
+        self.objects[index]
+            .shape
+            .intersect(ray)
+            .into_iter()
+            .map(|inter| inter.with_target(index as IntersectTargetID))
+            .collect()
+    }
+
+    // built on first use after objects changed
+    fn bvh(&self) -> &Bvh {
+        self.bvh.get_or_init(|| {
+            let bounds: Vec<Aabb> = self
+                .objects
+                .iter()
+                .map(|object| object.shape.bounds())
+                .collect();
+            Bvh::new(&bounds)
+        })
+    }
+}
+
+impl Default for TraceScene {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,229 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+use crate::physics::color::Color;
+
+use super::canvas::Canvas;
+
+const DEFAULT_TILE_SIZE: u32 = 32;
+
+/// stops a render in flight, clones share the same flag
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderProgress {
+    pub finished_tiles: usize,
+    pub tile_count: usize,
+}
+
+impl RenderProgress {
+    /// from 0.0 to 1.0
+    pub fn fraction(&self) -> f32 {
+        if self.tile_count == 0 {
+            1.0
+        } else {
+            self.finished_tiles as f32 / self.tile_count as f32
+        }
+    }
+}
+
+/// rectangle of pixels rendered by one thread at a time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// splits an image into tiles shared by worker threads, every pixel is
+/// computed on its own so the image is the same for any thread count
+#[derive(Debug, Clone, Copy)]
+pub struct TileRenderer {
+    tile_size: u32,
+    thread_count: usize,
+}
+
+impl TileRenderer {
+    /// one thread for each available core
+    pub fn new() -> Self {
+        Self {
+            tile_size: DEFAULT_TILE_SIZE,
+            thread_count: thread::available_parallelism().map_or(1, |count| count.get()),
+        }
+    }
+
+    pub fn with_tile_size(mut self, tile_size: u32) -> Self {
+        self.tile_size = tile_size.max(1);
+        self
+    }
+
+    pub fn with_thread_count(mut self, thread_count: usize) -> Self {
+        self.thread_count = thread_count.max(1);
+        self
+    }
+
+    pub fn get_thread_count(&self) -> usize {
+        self.thread_count
+    }
+
+    /// tiles row by row from the top left
+    pub fn tiles(&self, width: u32, height: u32) -> Vec<Tile> {
+        (0..height)
+            .step_by(self.tile_size as usize)
+            .flat_map(|y| {
+                (0..width)
+                    .step_by(self.tile_size as usize)
+                    .map(move |x| Tile {
+                        x,
+                        y,
+                        width: self.tile_size.min(width - x),
+                        height: self.tile_size.min(height - y),
+                    })
+            })
+            .collect()
+    }
+
+    /// color of pixel (x, y) from `shade`, `progress` is called on this thread
+    /// after each finished tile, Err if cancelled before all tiles are done
+    pub fn render(
+        &self,
+        width: u32,
+        height: u32,
+        shade: impl Fn(u32, u32) -> Color + Sync,
+        mut progress: impl FnMut(RenderProgress),
+        cancel: &CancelToken,
+    ) -> Result<Canvas, String> {
+        let tiles = self.tiles(width, height);
+        let next_tile = AtomicUsize::new(0);
+        let mut canvas = Canvas::new(width, height);
+        let mut finished_tiles = 0;
+
+        thread::scope(|scope| {
+            let (sender, receiver) = mpsc::channel::<(Tile, Vec<Color>)>();
+            (0..self.thread_count.min(tiles.len())).for_each(|_| {
+                let sender = sender.clone();
+                let (tiles, next_tile, shade) = (&tiles, &next_tile, &shade);
+                scope.spawn(move || {
+                    while let Some(tile) = tiles.get(next_tile.fetch_add(1, Ordering::Relaxed)) {
+                        let Some(pixels) = render_tile(tile, shade, cancel) else {
+                            return;
+                        };
+                        if sender.send((*tile, pixels)).is_err() {
+                            return;
+                        }
+                    }
+                });
+            });
+            // ends the loop below once all workers are done
+            drop(sender);
+
+            receiver.iter().for_each(|(tile, pixels)| {
+                (0..tile.height).for_each(|row| {
+                    (0..tile.width).for_each(|column| {
+                        let color = pixels[(row * tile.width + column) as usize];
+                        canvas
+                            .set_pixel(tile.x + column, tile.y + row, color)
+                            .unwrap();
+                    })
+                });
+                finished_tiles += 1;
+                progress(RenderProgress {
+                    finished_tiles,
+                    tile_count: tiles.len(),
+                });
+            });
+        });
+
+        if finished_tiles < tiles.len() {
+            Err("render cancelled".to_string())
+        } else {
+            Ok(canvas)
+        }
+    }
+}
+
+impl Default for TileRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// None if cancelled, checked between rows
+fn render_tile(
+    tile: &Tile,
+    shade: &impl Fn(u32, u32) -> Color,
+    cancel: &CancelToken,
+) -> Option<Vec<Color>> {
+    let mut pixels = Vec::with_capacity((tile.width * tile.height) as usize);
+    for y in tile.y..tile.y + tile.height {
+        if cancel.is_cancelled() {
+            return None;
+        }
+        pixels.extend((tile.x..tile.x + tile.width).map(|x| shade(x, y)));
+    }
+    Some(pixels)
+}
+
+#[test]
+fn same_for_any_thread_count() {
+    let shade = |x: u32, y: u32| Color::rgb(x as f32 / 7.0, y as f32 / 5.0, (x * y) as f32);
+    let render = |thread_count: usize| {
+        TileRenderer::new()
+            .with_tile_size(3)
+            .with_thread_count(thread_count)
+            .render(10, 7, shade, |_| {}, &CancelToken::new())
+            .unwrap()
+    };
+    let single = render(1);
+    assert_eq!(single.get_pixel(9, 6), Some(shade(9, 6)));
+    let multiple = render(4);
+    assert!(
+        single
+            .get_pixels()
+            .iter()
+            .zip(multiple.get_pixels())
+            .all(|(a, b)| a.get_value() == b.get_value())
+    );
+
+    // cancelled while the first tile is rendered
+    let cancel = CancelToken::new();
+    let mut finished_tiles = 0;
+    let result = TileRenderer::new().with_tile_size(2).render(
+        64,
+        64,
+        |x, y| {
+            if x == 0 && y == 0 {
+                cancel.cancel();
+            }
+            shade(x, y)
+        },
+        |progress| finished_tiles = progress.finished_tiles,
+        &cancel,
+    );
+    assert!(result.is_err());
+    assert!(finished_tiles < 32 * 32);
+}
@@ -0,0 +1,322 @@
+use crate::{
+    constant::REFLECTION_LIMIT,
+    content::scene::Camera,
+    math::geometry::{
+        common::{IntersectTargetID, Intersection, Intersections},
+        ray::Ray,
+    },
+    physics::{
+        color::Color,
+        common::{Illuminated, schlick},
+        material::Material,
+    },
+};
+
+use super::{
+    canvas::Canvas,
+    sampling::point_rng,
+    scene::TraceScene,
+    tile::{CancelToken, RenderProgress, TileRenderer},
+};
+
+/// recursive ray tracer with hard shadows, mirror reflection and refraction
+pub struct WhittedTracer<'s> {
+    scene: &'s TraceScene,
+    reflection_limit: usize,
+}
+
+impl<'s> WhittedTracer<'s> {
+    pub fn new(scene: &'s TraceScene) -> Self {
+        Self {
+            scene,
+            reflection_limit: REFLECTION_LIMIT,
+        }
+    }
+
+    pub fn with_reflection_limit(mut self, reflection_limit: usize) -> Self {
+        self.reflection_limit = reflection_limit;
+        self
+    }
+
+    pub fn render(&self, camera: &Camera, width: u32, height: u32) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        (0..height).for_each(|y| {
+            (0..width).for_each(|x| {
+                let color = self.pixel_color(camera, x, y, width, height);
+                canvas.set_pixel(x, y, color).unwrap();
+            })
+        });
+        canvas
+    }
+
+    /// same image as `render` on worker threads, Err if cancelled
+    pub fn render_tiled(
+        &self,
+        camera: &Camera,
+        width: u32,
+        height: u32,
+        renderer: &TileRenderer,
+        progress: impl FnMut(RenderProgress),
+        cancel: &CancelToken,
+    ) -> Result<Canvas, String> {
+        renderer.render(
+            width,
+            height,
+            |x, y| self.pixel_color(camera, x, y, width, height),
+            progress,
+            cancel,
+        )
+    }
+
+    fn pixel_color(&self, camera: &Camera, x: u32, y: u32, width: u32, height: u32) -> Color {
+        camera
+            .ray_for_pixel(x, y, width, height)
+            .map(|ray| self.color_at(&ray))
+            .unwrap_or(self.scene.background)
+    }
+
+    pub fn color_at(&self, ray: &Ray) -> Color {
+        self.trace(ray, self.reflection_limit)
+    }
+
+    // remaining: reflection and refraction bounces left
+    fn trace(&self, ray: &Ray, remaining: usize) -> Color {
+        let hits = self.scene.intersections(ray);
+        let Some((hit, material)) = hits
+            .hit()
+            .and_then(|hit| self.material_of(hit).map(|material| (hit, material)))
+        else {
+            return self.scene.background;
+        };
+        let normal = hit.get_shading_normal();
+        let eye_v = hit.get_eye_v();
+        let over_point = hit.get_over_point();
+
+        // jitter depends on the point only, the same for any render order
+        let mut rng = point_rng(0, &over_point);
+        let surface = material.shade(self.scene.get_lights(), hit, &mut |light| {
+            self.scene.visibility(&over_point, light, &mut rng)
+        });
+
+        if remaining == 0 || !(material.reflective() || material.transparent()) {
+            return surface;
+        }
+        let reflected = eye_v
+            .reflect(&normal)
+            .and_then(|direction| Ray::new(over_point, direction))
+            .map(|reflect_ray| self.trace(&reflect_ray, remaining - 1))
+            .unwrap_or(Color::black());
+        if !material.transparent() {
+            return surface + material.reflect_light(&reflected);
+        }
+
+        let (n_1, n_2) = self.refractive_indices(&hits);
+        // None for total internal reflection
+        let refracted = eye_v
+            .refract(&normal, n_1 / n_2)
+            .and_then(|direction| Ray::new(hit.get_under_point(), direction).ok())
+            .map(|refract_ray| self.trace(&refract_ray, remaining - 1))
+            .unwrap_or(Color::black());
+        // light passing the surface is split into reflection and refraction,
+        // the Fresnel reflection does not depend on reflectiveness
+        let reflectance = schlick(eye_v.dot(&normal), n_1, n_2);
+        surface + reflected * reflectance + material.refract_light(&refracted) * (1.0 - reflectance)
+    }
+
+    fn material_of(&self, inter: &Intersection) -> Option<&Material> {
+        inter
+            .get_target()
+            .and_then(|target| self.scene.get_object(target))
+            .map(|object| &object.material)
+    }
+
+    // refractive index on both sides of the hit, by tracking the
+    // objects the ray is inside of
+    fn refractive_indices(&self, hits: &Intersections) -> (f32, f32) {
+        let index_of = |containers: &[IntersectTargetID]| {
+            containers
+                .last()
+                .and_then(|target| self.scene.get_object(*target))
+                .map(|object| object.material.refractive_index())
+                .unwrap_or(1.0)
+        };
+        let hit_at = hits.hit_index().unwrap_or(0);
+        let mut containers: Vec<IntersectTargetID> = vec![];
+        hits.iter()
+            .take(hit_at)
+            .for_each(|inter| toggle(&mut containers, inter));
+        let n_1 = index_of(&containers);
+        if let Some(hit) = hits.get(hit_at) {
+            toggle(&mut containers, hit);
+        }
+        (n_1, index_of(&containers))
+    }
+}
+
+// enter or leave the object
+fn toggle(containers: &mut Vec<IntersectTargetID>, inter: &Intersection) {
+    if let Some(target) = inter.get_target() {
+        match containers.iter().position(|item| *item == target) {
+            Some(position) => {
+                containers.remove(position);
+            }
+            None => containers.push(target),
+        }
+    }
+}
+
+#[test]
+fn reflect_and_shadow() {
+    use crate::math::{
+        algebra::{point::Point, vector::Vector},
+        geometry::{plane::Plane, sphere::Sphere},
+    };
+    use crate::physics::{light::PointLight, phong::Phong};
+
+    let mut scene = TraceScene::new();
+    scene.add_object(
+        Box::new(Plane::new(Point::origin(), Vector::unit_y(), 100.0).unwrap()),
+        Phong::default(),
+    );
+    scene.add_object(
+        Box::new(Sphere::new(Point::point(0.0, 1.0, 0.0), 1.0)),
+        Phong::metal(),
+    );
+    scene.add_light(PointLight::new(
+        Point::point(0.0, 10.0, 0.0),
+        Color::white(),
+    ));
+    let tracer = WhittedTracer::new(&scene);
+
+    // under the sphere only ambient light
+    let ray = Ray::new(
+        Point::point(0.5, 5.0, -10.0),
+        Vector::vector(0.0, -5.0, 10.0),
+    )
+    .unwrap();
+    assert_eq!(tracer.color_at(&ray), Color::rgb(0.1, 0.1, 0.1));
+
+    // lit plane outside of the shadow
+    let ray = Ray::new(Point::point(3.0, 5.0, 0.0), Vector::vector(0.0, -1.0, 0.0)).unwrap();
+    assert!(tracer.color_at(&ray).get_r() > 0.5);
+
+    // the metal sphere is black but reflects the lit plane
+    let ray = Ray::new(Point::point(0.0, 1.0, -5.0), Vector::vector(0.0, -0.1, 1.0)).unwrap();
+    let reflected = tracer.color_at(&ray);
+    let direct = tracer.with_reflection_limit(0).color_at(&ray);
+    assert!(reflected.get_r() > direct.get_r());
+}
+
+#[test]
+fn soft_shadow() {
+    use crate::math::{
+        algebra::{point::Point, vector::Vector},
+        geometry::{plane::Plane, sphere::Sphere},
+    };
+    use crate::physics::{light::AreaLight, phong::Phong};
+
+    let mut scene = TraceScene::new();
+    scene.add_object(
+        Box::new(Plane::new(Point::origin(), Vector::unit_y(), 100.0).unwrap()),
+        Phong::default(),
+    );
+    scene.add_object(
+        Box::new(Sphere::new(Point::point(0.0, 2.0, 0.0), 1.0)),
+        Phong::default(),
+    );
+    let light = AreaLight::rectangle(
+        Point::point(-2.0, 6.0, -2.0),
+        Vector::vector(4.0, 0.0, 0.0),
+        Vector::vector(0.0, 0.0, 4.0),
+        Color::white(),
+    )
+    .with_samples(64);
+    scene.add_light(light);
+    let mut rng = point_rng(0, &Point::origin());
+    let visibility =
+        |x: f32, rng: &mut _| scene.visibility(&Point::point(x, 0.001, 0.0), &light, rng);
+    // umbra under the sphere, penumbra at the edge, lit far away
+    assert_eq!(visibility(0.0, &mut rng), 0.0);
+    let penumbra = visibility(1.6, &mut rng);
+    assert!(penumbra > 0.0 && penumbra < 1.0);
+    assert_eq!(visibility(6.0, &mut rng), 1.0);
+
+    let tracer = WhittedTracer::new(&scene);
+    let ray =
+        |x: f32| Ray::new(Point::point(x, 1.0, -5.0), Vector::vector(0.0, -1.0, 5.0)).unwrap();
+    let (umbra, edge, lit) = (
+        tracer.color_at(&ray(0.0)),
+        tracer.color_at(&ray(1.6)),
+        tracer.color_at(&ray(6.0)),
+    );
+    assert!(umbra.get_r() < edge.get_r() && edge.get_r() < lit.get_r());
+}
+
+#[test]
+fn refract_through_glass() {
+    use crate::math::{
+        algebra::{point::Point, vector::Vector},
+        geometry::{cube::Cube, plane::Plane, sphere::Sphere},
+    };
+    use crate::physics::phong::Phong;
+
+    // red in front of the camera, a green wall behind it
+    let mut scene = TraceScene::new().with_background(Color::rgb(1.0, 0.0, 0.0));
+    scene.add_object(Box::new(Sphere::new(Point::origin(), 1.0)), Phong::glass());
+    scene.add_object(
+        Box::new(Plane::new(Point::point(0.0, 0.0, -10.0), Vector::unit_z(), 100.0).unwrap()),
+        Phong::default().with_emission(Color::rgb(0.0, 1.0, 0.0)),
+    );
+    let tracer = WhittedTracer::new(&scene);
+
+    // straight through the center, about 4% reflected back at the surfaces,
+    // the rest passes, no light is lost
+    let ray = Ray::new(Point::point(0.0, 0.0, -5.0), Vector::unit_z()).unwrap();
+    let color = tracer.color_at(&ray);
+    assert!(color.get_g() > 0.07 && color.get_r() > 0.9);
+    assert!((color.get_r() + color.get_g() - 1.0).abs() < 1e-2);
+    let direct = WhittedTracer::new(&scene).with_reflection_limit(0);
+    assert_eq!(direct.color_at(&ray), Color::black());
+
+    // inside a glass cube the top face reflects totally, the light
+    // comes in through the side face instead
+    let mut scene = TraceScene::new().with_background(Color::rgb(1.0, 0.0, 0.0));
+    scene.add_object(Box::new(Cube::new()), Phong::glass());
+    let tracer = WhittedTracer::new(&scene).with_reflection_limit(2);
+    let ray = Ray::new(Point::origin(), Vector::vector(0.0, 1.0, 1.2)).unwrap();
+    let cos_side = 1.2 / 2.44f32.sqrt();
+    let expected = 1.0 - schlick(cos_side, 1.5, 1.0);
+    assert!((tracer.color_at(&ray).get_r() - expected).abs() < 1e-4);
+}
+
+#[test]
+fn transparent_shadow() {
+    use crate::math::{
+        algebra::{point::Point, vector::Vector},
+        geometry::{plane::Plane, sphere::Sphere},
+    };
+    use crate::physics::{light::PointLight, phong::Phong};
+
+    let mut scene = TraceScene::new();
+    scene.add_object(
+        Box::new(Plane::new(Point::origin(), Vector::unit_y(), 100.0).unwrap()),
+        Phong::default(),
+    );
+    scene.add_object(
+        Box::new(Sphere::new(Point::point(0.0, 2.0, 0.0), 1.0)),
+        Phong::glass().with_transparency(0.5),
+    );
+    let light = PointLight::new(Point::point(0.0, 10.0, 0.0), Color::white());
+    scene.add_light(light);
+    let mut rng = point_rng(0, &Point::origin());
+    // dimmed once by the sphere, not per surface
+    let under = scene.visibility(&Point::point(0.0, 0.001, 0.0), &light, &mut rng);
+    assert!((under - 0.5).abs() < 1e-6);
+
+    scene.add_object(
+        Box::new(Sphere::new(Point::point(0.0, 5.0, 0.0), 1.0)),
+        Phong::default(),
+    );
+    let under = scene.visibility(&Point::point(0.0, 0.001, 0.0), &light, &mut rng);
+    assert_eq!(under, 0.0);
+}